@@ -29,6 +29,7 @@ fn main() {
     let args: Args = argh::from_env();
     App::new()
         .add_plugins(RavyPlugin { module: module_path!(), debug: args.debug, trace: args.trace })
+        .add_plugins((MidiPlugin::<LaunchpadX>::default(), MidiPlugin::<LaunchControlXL>::default()))
         .add_systems(Startup, setup)
         .add_systems(Update, (on_pad, on_ctrl, tick, render_lights, render_pad).chain())
         .add_systems(EguiPrimaryContextPass, ui::draw)
@@ -37,13 +38,14 @@ fn main() {
 
 fn setup(mut cmds: Commands) -> Result {
     let ctrl = Midi::new("Launch Control XL", LaunchControlXL::default());
-    let mut pad = Midi::new("Launchpad X LPX MIDI", LaunchpadX::default());
-    {
+    let pad = {
         use launchpad_x::types::*;
         use launchpad_x::*;
-        pad.send(Output::Pressure(Pressure::Off, PressureCurve::Medium));
-        pad.send(Output::Brightness(0.0));
-    }
+        Midi::new("Launchpad X LPX MIDI", LaunchpadX::default()).on_connect(vec![
+            Output::Pressure(Pressure::Off, PressureCurve::Medium),
+            Output::Brightness(0.0),
+        ])
+    };
     cmds.insert_resource(pad);
     cmds.insert_resource(ctrl);
 
@@ -1256,14 +1258,19 @@ pub fn tick(mut s: ResMut<State>, mut l: ResMut<Lights>, time: Res<Time>) {
 
 ///////////////////////// PAD INPUT /////////////////////////
 
-pub fn on_pad(mut s: ResMut<State>, mut l: ResMut<Lights>, mut pad: ResMut<Midi<LaunchpadX>>) {
+pub fn on_pad(
+    mut s: ResMut<State>,
+    mut l: ResMut<Lights>,
+    mut inputs: EventReader<MidiInput<LaunchpadX>>,
+    mut pad: ResMut<Midi<LaunchpadX>>,
+) {
     let s: &mut State = &mut *s;
     let l: &mut Lights = &mut *l;
 
     use launchpad_x::types::*;
     use launchpad_x::*;
 
-    for event in pad.recv() {
+    for event in inputs.read().map(|e| e.input) {
         use self::Mode;
         // debug!("pad: {event:?}");
 
@@ -1576,11 +1583,15 @@ pub fn on_pad(mut s: ResMut<State>, mut l: ResMut<Lights>, mut pad: ResMut<Midi<
 
 ///////////////////////// CTRL INPUT /////////////////////////
 
-pub fn on_ctrl(mut s: ResMut<State>, mut l: ResMut<Lights>, mut ctrl: ResMut<Midi<LaunchControlXL>>) {
+pub fn on_ctrl(
+    mut s: ResMut<State>,
+    mut l: ResMut<Lights>,
+    mut ctrl: EventReader<MidiInput<LaunchControlXL>>,
+) {
     let s: &mut State = &mut *s;
     let l: &mut Lights = &mut *l;
 
-    for input in ctrl.recv() {
+    for input in ctrl.read().map(|e| e.input) {
         use launch_control_xl::types::*;
         use launch_control_xl::*;
         debug!("ctrl: {input:?}");
@@ -1,3 +1,4 @@
+use lib::dmx::rdm::{DeviceInfo, RdmController, RdmUdp, Uid};
 use lib::midi::device::launch_control_xl::{self, LaunchControlXL};
 use lib::prelude::*;
 
@@ -10,22 +11,39 @@ struct Args {
     /// enable trace logging
     #[argh(switch, short = 'V')]
     trace: bool,
+    /// display a universe received over E1.31 or Art-Net instead of sending
+    #[argh(option)]
+    sniff: Option<u16>,
+    /// address to send RDM requests to, e.g. a broadcast address
+    #[argh(option, default = "String::from(\"127.0.0.1\")")]
+    rdm: String,
 }
 
 fn main() -> Result {
     let args: Args = argh::from_env();
-    App::new()
-        .add_plugins(RavyPlugin { module: module_path!(), debug: args.debug, trace: args.trace })
+    let mut app = App::new();
+    app.add_plugins(RavyPlugin { module: module_path!(), debug: args.debug, trace: args.trace })
+        .add_plugins(MidiPlugin::<LaunchControlXL>::default())
         .add_systems(Startup, setup)
-        .add_systems(Update, (on_ctrl, render_ctrl, tick, render_lights).chain())
-        .add_systems(EguiPrimaryContextPass, draw_ui)
+        .add_systems(EguiPrimaryContextPass, (draw_ui, draw_rdm))
+        .insert_resource(Rdm::new(&args.rdm)?)
         .insert_resource(Midi::new("Launch Control XL", LaunchControlXL::default()))
-        .insert_resource(E131::new("10.16.4.1")?)
         .insert_resource(State::default().tap_mut(|s| {
-            s.dmx.resize(256, 0);
+            // Room for a whole universe when sniffing.
+            s.dmx.resize(if args.sniff.is_some() { 512 } else { 256 }, 0);
             s.device.resize(1, 0);
-        }))
-        .run();
+        }));
+
+    match args.sniff {
+        Some(universe) => app
+            .add_systems(Update, (on_ctrl, render_ctrl, tick, sniff).chain())
+            .insert_resource(DmxReceiver::new(&[universe])?),
+        None => app
+            .add_systems(Update, (on_ctrl, render_ctrl, tick, render_lights).chain())
+            .insert_resource(E131::new("10.16.4.1")?),
+    };
+
+    app.run();
     Ok(())
 }
 
@@ -71,6 +89,44 @@ pub enum DeviceType {
     Strobe, // (5  /  5ch?) Blizzard Max-L
 }
 
+/// RDM controller and the devices it's found.
+#[derive(Resource)]
+pub struct Rdm {
+    controller: RdmController<RdmUdp>,
+    devices: Vec<RdmDevice>,
+    /// Last error, shown until the next request.
+    error: Option<String>,
+}
+
+pub struct RdmDevice {
+    uid: Uid,
+    info: DeviceInfo,
+    /// Start address being edited.
+    address: u16,
+    identify: bool,
+}
+
+impl Rdm {
+    /// Under the prototyping manufacturer ID, reserved by ESTA for devices that aren't for sale.
+    const UID: Uid = Uid::new(0x7FF0, 0);
+
+    fn new(dest_ip: &str) -> Result<Self> {
+        let controller = RdmController::new(RdmUdp::new(dest_ip)?, Self::UID);
+        Ok(Self { controller, devices: vec![], error: None })
+    }
+
+    /// Find every device and read its info. Blocks the frame until discovery finishes.
+    fn discover(&mut self) -> Result {
+        self.devices.clear();
+        for uid in self.controller.discover()? {
+            let info = self.controller.device_info(uid)?;
+            self.devices
+                .push(RdmDevice { uid, info, address: info.start_address, identify: false });
+        }
+        Ok(())
+    }
+}
+
 ///////////////////////// TICK /////////////////////////
 
 pub fn tick(mut s: ResMut<State>, t: Res<Time>) {
@@ -150,12 +206,26 @@ pub fn render_lights(mut s: ResMut<State>, mut e131: ResMut<E131>) {
     e131.send(&s.device);
 }
 
+/// Display the latest frame from the network instead of rendering our own.
+pub fn sniff(mut s: ResMut<State>, receiver: Res<DmxReceiver>) {
+    let Some(frame) = receiver.frames().next() else {
+        return;
+    };
+
+    // Skip the start code. Short frames are padded, since shrinking the buffer could leave the
+    // device past its end.
+    let channels = frame.data.get(1..).unwrap_or_default();
+    let n = channels.len().min(s.dmx.len());
+    s.dmx.fill(0);
+    s.dmx[..n].copy_from_slice(&channels[..n]);
+}
+
 ///////////////////////// CTRL INPUT /////////////////////////
 
-pub fn on_ctrl(mut s: ResMut<State>, mut ctrl: ResMut<Midi<LaunchControlXL>>) {
+pub fn on_ctrl(mut s: ResMut<State>, mut ctrl: EventReader<MidiInput<LaunchControlXL>>) {
     use launch_control_xl::*;
 
-    for input in ctrl.recv() {
+    for input in ctrl.read().map(|e| e.input) {
         debug!("ctrl: {input:?}");
 
         let device_len = s.device.len();
@@ -173,7 +243,7 @@ pub fn on_ctrl(mut s: ResMut<State>, mut ctrl: ResMut<Midi<LaunchControlXL>>) {
 
             Input::TrackSelect(false, true) => s.device_channel = s.device_channel.saturating_sub(shift),
             Input::TrackSelect(true, true) => {
-                s.device_channel = (s.device_channel + shift).min(s.dmx.len() - s.device.len())
+                s.device_channel = (s.device_channel + shift).min(s.dmx.len().saturating_sub(s.device.len()))
             }
 
             Input::SendA(i, fr) => {
@@ -348,3 +418,58 @@ pub fn draw_ui(mut ctxs: EguiContexts, s: Res<State>) -> Result {
 
     Ok(())
 }
+
+pub fn draw_rdm(mut ctxs: EguiContexts, mut rdm: ResMut<Rdm>) -> Result {
+    let ctx = ctxs.ctx_mut()?;
+    let rdm = &mut *rdm;
+
+    egui::Window::new("RDM").show(ctx, |ui| {
+        if ui.button("Discover").clicked() {
+            rdm.error = rdm.discover().err().map(|e| e.to_string());
+        }
+        if let Some(error) = &rdm.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        ui.separator();
+
+        egui::Grid::new("rdm_devices").striped(true).show(ui, |ui| {
+            ui.label("UID");
+            ui.label("Model");
+            ui.label("Footprint");
+            ui.label("Address");
+            ui.label("");
+            ui.label("");
+            ui.end_row();
+
+            for device in &mut rdm.devices {
+                ui.label(device.uid.to_string());
+                ui.label(format!("{:04X}", device.info.model_id));
+                ui.label(device.info.footprint.to_string());
+
+                let last = 512u16.saturating_sub(device.info.footprint.max(1) - 1).max(1);
+                ui.add(egui::DragValue::new(&mut device.address).range(1..=last));
+                if ui.button("Set").clicked() {
+                    let result = rdm.controller.set_start_address(device.uid, device.address);
+                    match result.and_then(|_| rdm.controller.device_info(device.uid)) {
+                        Ok(info) => {
+                            device.info = info;
+                            rdm.error = None;
+                        }
+                        Err(e) => rdm.error = Some(format!("{e:#}")),
+                    }
+                    device.address = device.info.start_address;
+                }
+
+                if ui.toggle_value(&mut device.identify, "Identify").changed()
+                    && let Err(e) = rdm.controller.identify(device.uid, device.identify)
+                {
+                    rdm.error = Some(format!("{e:#}"));
+                    device.identify = !device.identify;
+                }
+                ui.end_row();
+            }
+        });
+    });
+
+    Ok(())
+}
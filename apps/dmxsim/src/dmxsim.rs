@@ -5,6 +5,7 @@ use bevy::core_pipeline::bloom::Bloom;
 use bevy::core_pipeline::tonemapping::{DebandDither, Tonemapping};
 use bevy::math::primitives::{ConicalFrustum, Cuboid, Cylinder, Sphere};
 use egui_plot::{Line, Plot, PlotPoints}; // <- plotting
+use lib::dmx::rdm::{RdmResponder, RdmUdpResponder, Uid};
 use lib::prelude::*; // assumes this exports bevy, bevy_egui, etc.
 
 //
@@ -32,14 +33,23 @@ struct Args {
     /// enable trace logging
     #[argh(switch, short = 'V')]
     trace: bool,
+    /// drive the fixture from a DMX recording instead of the test pattern
+    #[argh(option)]
+    replay: Option<String>,
 }
 
 fn main() -> Result {
     let args: Args = argh::from_env();
-    App::new()
-        .add_plugins(RavyPlugin { module: module_path!(), debug: args.debug, trace: args.trace })
+    let mut app = App::new();
+    match &args.replay {
+        Some(path) => app
+            .add_systems(PreUpdate, apply_replay.after(DmxDecode))
+            .insert_resource(DmxVisualizer::with_player(DmxPlayer::open(path)?)),
+        None => app.add_systems(PreUpdate, apply_pattern),
+    };
+
+    app.add_plugins(RavyPlugin { module: module_path!(), debug: args.debug, trace: args.trace })
         .add_systems(Startup, setup)
-        .add_systems(PreUpdate, apply_pattern)
         .add_systems(
             Update,
             (
@@ -47,11 +57,13 @@ fn main() -> Result {
                 advance_test_queue,
                 drive_motion,
                 apply_pose_to_scene,
+                sync_rdm,
                 send_dmx_if_enabled,
             ),
         )
         .add_systems(EguiPrimaryContextPass, draw_ui)
-        .insert_resource(E131::new("10.16.4.1")?)
+        .insert_resource(DmxScheduler::new(E131::new("10.16.4.1")?))
+        .insert_resource(RdmUdpResponder::new(vec![rdm_device(&SimState::default())])?)
         .insert_resource(SimState::default())
         .insert_resource(TestQueue::default())
         .run();
@@ -70,6 +82,19 @@ fn apply_pattern(mut sim: ResMut<SimState>, t: Res<Time>) {
     sim.target_yaw_norm = yaw - 0.25 / 1.5;
 }
 
+/// Drive the fixture from a DMX recording played by the `DmxVisualizer`, instead of `apply_pattern()`.
+fn apply_replay(mut sim: ResMut<SimState>, visualizer: Res<DmxVisualizer>) {
+    let Some(frame) = visualizer.frame(1) else {
+        return;
+    };
+
+    let ch = |ch: u16| frame.get(ch as usize).copied().unwrap_or(0) as f32 / 255.0;
+    sim.target_yaw_norm = ch(sim.yaw_ch);
+    sim.target_pitch_norm = ch(sim.pitch_ch);
+    let base = sim.rgbw_start;
+    sim.color_rgbw = [ch(base), ch(base + 1), ch(base + 2), ch(base + 3)];
+}
+
 //
 // ---------- Data model & helpers ----------
 //
@@ -136,6 +161,8 @@ struct SimState {
     pitch_ch: u16,        // 1..=512
     dimmer_ch: u16,       // master dimmer channel (255 always)
     rgbw_start: u16,      // start of R,G,B,W consecutive block
+    rdm_address: u16,     // start address last synced with the RDM responder
+    identify: bool,       // set over RDM to flash the fixture
     color_rgbw: [f32; 4], // 0..=1 (we'll use RGB only; W ignored)
 
     // Normalized motion targets (0..1)
@@ -189,6 +216,17 @@ struct SimState {
     spawned_kind: Option<FixtureKind>,
 }
 
+impl SimState {
+    /// The fixture color, flashing white once a second while identifying over RDM.
+    fn rgbw(&self, t: f32) -> [f32; 4] {
+        match self.identify {
+            true if t.fract() < 0.5 => [1.0; 4],
+            true => [0.0; 4],
+            false => self.color_rgbw,
+        }
+    }
+}
+
 impl Default for SimState {
     fn default() -> Self {
         Self {
@@ -200,6 +238,8 @@ impl Default for SimState {
             pitch_ch: 83,
             dimmer_ch: 85,
             rgbw_start: 87,
+            rdm_address: 82,
+            identify: false,
 
             color_rgbw: [1.0, 1.0, 1.0, 0.0], // white by default (W ignored/sent as 0)
 
@@ -678,6 +718,7 @@ fn apply_pose_to_scene(
     mut mats: ResMut<Assets<StandardMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
     state: Res<SimState>,
+    time: Res<Time>,
 ) {
     // Root (yaw around +Y)
     if let Ok(mut t_root) = sets.p0().single_mut() {
@@ -695,7 +736,7 @@ fn apply_pose_to_scene(
     let inner = (outer * 0.6).min(outer - eps).max(0.0);
 
     if let Ok(mut spot) = sets.p2().single_mut() {
-        let [r, g, b, _w_unused] = state.color_rgbw;
+        let [r, g, b, _w_unused] = state.rgbw(time.elapsed_secs());
         spot.color = Color::srgb(r, g, b);
         spot.outer_angle = outer;
         spot.inner_angle = inner;
@@ -705,7 +746,7 @@ fn apply_pose_to_scene(
     // Lens emissive tint (bloom)
     if let Ok(BeamLensMat(h)) = lens_q.single() {
         if let Some(m) = mats.get_mut(h) {
-            let [r, g, b, _w_unused] = state.color_rgbw;
+            let [r, g, b, _w_unused] = state.rgbw(time.elapsed_secs());
             m.emissive = Color::srgb(r * 6.0, g * 6.0, b * 6.0).into();
         }
     }
@@ -713,7 +754,7 @@ fn apply_pose_to_scene(
     // Frustum: recolor + rebuild mesh if needed; keep center where we want it
     if let Ok((BeamFrustumMesh(mesh_h), BeamFrustumMat(mat_h), mut t)) = sets.p3().single_mut() {
         if let Some(m) = mats.get_mut(mat_h) {
-            let [r, g, b, _w_unused] = state.color_rgbw;
+            let [r, g, b, _w_unused] = state.rgbw(time.elapsed_secs());
             m.base_color = Color::srgba(r, g, b, 0.18);
         }
 
@@ -732,11 +773,54 @@ fn apply_pose_to_scene(
     }
 }
 
+//
+// ---------- RDM ----------
+//
+
+/// Under the prototyping manufacturer ID, reserved by ESTA for devices that aren't for sale.
+const RDM_UID: Uid = Uid::new(0x7FF0, 1);
+
+/// Simulated RDM responder for the fixture, so `dmxplorer` can find and readdress it.
+fn rdm_device(state: &SimState) -> RdmResponder {
+    let mut device = RdmResponder::new(RDM_UID, footprint(state), state.yaw_ch);
+    device.model_id = 1;
+    device
+}
+
+/// Channels used by the fixture, starting from the yaw channel.
+fn footprint(state: &SimState) -> u16 {
+    let last = state.pitch_ch.max(state.dimmer_ch).max(state.rgbw_start + 3);
+    last.max(state.yaw_ch) - state.yaw_ch + 1
+}
+
+/// Keep the fixture channels and the RDM start address in sync, whichever side changed.
+fn sync_rdm(mut state: ResMut<SimState>, rdm: Res<RdmUdpResponder>) {
+    let mut devices = rdm.devices();
+    let Some(device) = devices.first_mut() else {
+        return;
+    };
+
+    if device.start_address != state.rdm_address {
+        // Readdressed over RDM, move every channel along with the start address.
+        let delta = device.start_address as i32 - state.rdm_address as i32;
+        let shift = |ch: u16| (ch as i32 + delta).clamp(1, 512) as u16;
+        state.yaw_ch = shift(state.yaw_ch);
+        state.pitch_ch = shift(state.pitch_ch);
+        state.dimmer_ch = shift(state.dimmer_ch);
+        state.rgbw_start = shift(state.rgbw_start).min(509);
+    } else if state.yaw_ch != state.rdm_address {
+        device.start_address = state.yaw_ch;
+    }
+    state.rdm_address = device.start_address;
+    device.footprint = footprint(&state);
+    state.identify = device.identify;
+}
+
 //
 // ---------- DMX output ----------
 //
 
-fn send_dmx_if_enabled(mut e131: ResMut<E131>, state: Res<SimState>) {
+fn send_dmx_if_enabled(mut dmx: ResMut<DmxScheduler>, state: Res<SimState>, time: Res<Time>) {
     if !state.dmx_enabled {
         return;
     }
@@ -761,7 +845,7 @@ fn send_dmx_if_enabled(mut e131: ResMut<E131>, state: Res<SimState>) {
 
     // RGB only (W ignored/sent as 0)
     let base = state.rgbw_start as usize;
-    let [r, g, b, _w_unused] = state.color_rgbw;
+    let [r, g, b, _w_unused] = state.rgbw(time.elapsed_secs());
     let mut push = |i: usize, v: f32| {
         if i < 512 {
             universe[i] = dmx_byte_clamped(v * 255.0);
@@ -774,7 +858,8 @@ fn send_dmx_if_enabled(mut e131: ResMut<E131>, state: Res<SimState>) {
         universe[base + 3] = 0; // W forced to 0
     }
 
-    let _ = e131.send(&mut universe);
+    dmx.send_universe(1, &universe);
+    dmx.flush();
 }
 
 //
@@ -1149,6 +1234,14 @@ fn draw_ui(mut egui_ctx: EguiContexts, mut state: ResMut<SimState>, mut tests: R
             ui.add(egui::DragValue::new(&mut state.rgbw_start).range(1..=509));
             ui.label("(uses 4 consecutive slots; W forced to 0)");
         });
+
+        ui.heading("RDM");
+        ui.horizontal(|ui| {
+            ui.label(format!("UID {RDM_UID}, start address {}", state.rdm_address));
+            if state.identify {
+                ui.colored_label(egui::Color32::YELLOW, "Identifying");
+            }
+        });
     });
     Ok(())
 }
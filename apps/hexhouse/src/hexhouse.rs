@@ -1,8 +1,10 @@
 use bevy::core_pipeline::bloom::Bloom;
 use bevy::core_pipeline::tonemapping::Tonemapping;
 use lib::lights::fixture::{SaberSpot, StealthBeam};
+use lib::midi::clock::ClockMessage;
 use lib::midi::device::launch_control_xl::LaunchControlXL;
 use lib::midi::device::launchpad_x::{self, LaunchpadX};
+use lib::midi::{ClockOut, MidiClock, MidiClockPlugin};
 use lib::prelude::*;
 
 mod lights;
@@ -51,12 +53,53 @@ struct Args {
     /// enable trace logging
     #[argh(switch, short = 'V')]
     trace: bool,
+    /// send DMX to an Enttec DMX USB Pro at this serial device instead of over E1.31
+    #[argh(option)]
+    enttec: Option<String>,
+    /// record every DMX frame sent to this file, for replaying in dmxsim
+    #[argh(option)]
+    record: Option<String>,
+    /// visualize DMX received on universe 1 instead of running the show
+    #[argh(switch)]
+    visualize: bool,
+    /// visualize a recording from --record instead of running the show
+    #[argh(option)]
+    replay: Option<String>,
+    /// follow the tempo of MIDI clock from this port, e.g. a DJ mixer
+    #[argh(option)]
+    clock: Option<String>,
+    /// send MIDI clock at the current tempo to this port
+    #[argh(option)]
+    clock_out: Option<String>,
 }
 
-fn main() {
+fn main() -> Result {
     let args: Args = argh::from_env();
-    App::new()
-        .add_plugins(RavyPlugin { module: module_path!(), debug: args.debug, trace: args.trace })
+
+    let mut app = App::new();
+    match (&args.replay, args.visualize) {
+        (Some(path), _) => app.insert_resource(DmxVisualizer::with_player(DmxPlayer::open(path)?)),
+        (None, true) => app
+            .insert_resource(DmxVisualizer::new())
+            .insert_resource(DmxReceiver::new(&[1])?),
+        (None, false) => app.insert_resource(output(&args)?),
+    };
+
+    if let Some(port) = &args.clock {
+        app.insert_resource(Midi::new(port, MidiClock));
+    }
+    if let Some(port) = &args.clock_out {
+        // Not a resource, since `--clock` may be a different port. Sends are dropped until the
+        // port connects, so also start whatever's listening each time it does.
+        let mut midi = Midi::output(port, MidiClock).on_connect(vec![ClockMessage::Start]);
+        let clock_out = ClockOut::new(120.0, move |message| midi.send(message));
+        clock_out.start();
+        app.insert_resource(clock_out);
+    }
+
+    app.add_plugins(RavyPlugin { module: module_path!(), debug: args.debug, trace: args.trace })
+        .add_plugins((MidiPlugin::<LaunchpadX>::default(), MidiPlugin::<LaunchControlXL>::default()))
+        .add_plugins(MidiClockPlugin)
         .add_systems(Startup, setup)
         .add_systems(PreUpdate, setup_scene)
         .add_systems(
@@ -65,29 +108,41 @@ fn main() {
                 logic::on_pad,
                 logic::on_ctrl,
                 logic::tick,
-                logic::render_lights,
+                logic::render_lights.run_if(not(resource_exists::<DmxVisualizer>)),
                 logic::render_pad,
             )
                 .chain(),
         )
         .run();
+    Ok(())
+}
+
+fn output(args: &Args) -> Result<DmxScheduler> {
+    let mut out: Box<dyn DmxOutput + Send> = match &args.enttec {
+        Some(path) => Box::new(EnttecPro::new(path)?),
+        None => Box::new(E131::new("10.16.4.1")?),
+    };
+    if let Some(path) = &args.record {
+        out = Box::new((out, DmxRecorder::create(path)?));
+    }
+    Ok(DmxScheduler::new(out))
 }
 
 fn setup(mut cmds: Commands, assets: Res<AssetServer>) -> Result {
     // Resources
     cmds.insert_resource(logic::State::new());
-    cmds.insert_resource(E131::new("10.16.4.1")?);
     cmds.insert_resource(Synesthesia::new("0.0.0.0:0", "127.0.0.1:6000")?);
 
     // Control surfaces
     let ctrl = Midi::new("Launch Control XL", LaunchControlXL::default());
-    let mut pad = Midi::new("Launchpad X LPX MIDI", LaunchpadX::default());
-    {
+    let pad = {
         use launchpad_x::types::*;
         use launchpad_x::*;
-        pad.send(Output::Pressure(Pressure::Off, PressureCurve::Medium));
-        pad.send(Output::Brightness(0.0));
-    }
+        Midi::new("Launchpad X LPX MIDI", LaunchpadX::default()).on_connect(vec![
+            Output::Pressure(Pressure::Off, PressureCurve::Medium),
+            Output::Brightness(0.0),
+        ])
+    };
     cmds.insert_resource(pad);
     cmds.insert_resource(ctrl);
 
@@ -104,8 +159,6 @@ struct HexHouse;
 #[derive(Component)]
 struct HexHouseSetup;
 
-#[derive(Component)]
-struct FixtureChannel(usize);
 #[derive(Component)]
 struct FixtureIndex {
     i: usize,
@@ -132,14 +185,14 @@ fn setup_scene(
 
     for (name, entity) in scene.nodes().filter(|(name, _)| name.starts_with("Mover Ch.")) {
         debug!("Mover: {entity}");
-        let (chan, idx) = parse_fixture(name);
-        cmds.entity(entity).insert((StealthBeam::default(), chan, idx));
+        let (address, idx) = parse_fixture(name);
+        cmds.entity(entity).insert((StealthBeam::default(), address, idx));
     }
 
     for (name, entity) in scene.nodes().filter(|(name, _)| name.starts_with("Spot Ch.")) {
         debug!("Spot: {entity}");
-        let (chan, idx) = parse_fixture(name);
-        cmds.entity(entity).insert((SaberSpot::default(), chan, idx));
+        let (address, idx) = parse_fixture(name);
+        cmds.entity(entity).insert((SaberSpot::default(), address, idx));
     }
 
     for (_, entity) in scene.nodes().filter(|(name, _)| *name == "DiscoBall") {
@@ -150,12 +203,13 @@ fn setup_scene(
     cmds.entity(scene_ent).insert(HexHouseSetup);
 }
 
-fn parse_fixture(name: &str) -> (FixtureChannel, FixtureIndex) {
+/// Parse the DMX address and index from a fixture node name, e.g. "Mover Ch.1 ...".
+fn parse_fixture(name: &str) -> (DmxAddress, FixtureIndex) {
     let (_, parts) = name.split_once(' ').unwrap();
     let parts = parts.split(' ').collect::<Vec<_>>();
 
     let (_, chan) = parts[0].split_once('.').unwrap();
-    let chan = FixtureChannel(chan.parse().unwrap());
+    let address = DmxAddress { universe: 1, address: chan.parse().unwrap() };
 
     let (_, i) = parts[1].split_once('.').unwrap();
     let i: usize = i.parse().unwrap();
@@ -165,5 +219,5 @@ fn parse_fixture(name: &str) -> (FixtureChannel, FixtureIndex) {
     let col: usize = col.parse().unwrap();
     let idx = FixtureIndex { i, row, col };
 
-    (chan, idx)
+    (address, idx)
 }
@@ -1,7 +1,7 @@
 use lib::lights::fixture::{SaberSpot, StealthBeam};
 use lib::prelude::*;
 
-use crate::{DiscoBall, FixtureChannel, FixtureIndex};
+use crate::{DiscoBall, FixtureIndex};
 
 pub struct Lights<'a> {
     #[allow(unused)]
@@ -18,8 +18,8 @@ pub struct Lights<'a> {
 
 impl<'a> Lights<'a> {
     pub fn new<'w, 's>(
-        beams_q: Query<'w, 's, (&mut StealthBeam, &Transform, &FixtureChannel, &FixtureIndex)>,
-        spots_q: Query<'w, 's, (&mut SaberSpot, &Transform, &FixtureChannel, &FixtureIndex)>,
+        beams_q: Query<'w, 's, (&mut StealthBeam, &Transform, &FixtureIndex)>,
+        spots_q: Query<'w, 's, (&mut SaberSpot, &Transform, &FixtureIndex)>,
         disco_q: Query<'w, 's, &Transform, With<DiscoBall>>,
     ) -> Option<Lights<'a>>
     where
@@ -31,10 +31,9 @@ impl<'a> Lights<'a> {
         let disco = disco_q.single().ok()?.translation;
 
         let mut beams = vec![];
-        for (beam, transform, chan, idx) in beams_q {
+        for (beam, transform, idx) in beams_q {
             beams.push(Light {
                 light: beam,
-                channel: chan.0,
                 row: idx.row,
                 col: idx.col,
                 i: idx.i,
@@ -49,10 +48,9 @@ impl<'a> Lights<'a> {
         }
 
         let mut spots = vec![];
-        for (spot, transform, chan, idx) in spots_q {
+        for (spot, transform, idx) in spots_q {
             spots.push(Light {
                 light: spot,
-                channel: chan.0,
                 row: idx.row,
                 col: idx.col,
                 i: idx.i,
@@ -101,26 +99,12 @@ impl<'a> Lights<'a> {
         let n = slice.len();
         slice.iter_mut().enumerate().for_each(|(i, t)| f(t, i, i as f32 / n as f32));
     }
-
-    pub fn send(&self, e131: &mut E131) {
-        let mut dmx = vec![0; 232];
-
-        for Light { light, channel, .. } in &self.beams {
-            light.encode(&mut dmx[*channel..]);
-        }
-        for Light { light, channel, .. } in &self.spots {
-            light.encode(&mut dmx[*channel..]);
-        }
-
-        e131.send(&dmx);
-    }
 }
 
 #[allow(unused)]
 pub struct Light<'a, T> {
     light: Mut<'a, T>,
 
-    pub channel: usize,
     pub row: usize,
     pub col: usize,
     pub i: usize,
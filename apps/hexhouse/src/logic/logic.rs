@@ -1,8 +1,11 @@
+use std::time::Instant;
+
 use itertools::Itertools;
 // - [ ] port more shaders
 use lib::lights::fixture::{SaberSpot, StealthBeam};
 use lib::midi::device::launch_control_xl::{self, LaunchControlXL};
 use lib::midi::device::launchpad_x::{self, LaunchpadX};
+use lib::midi::{ClockOut, ClockSync};
 use lib::prelude::*;
 
 // use rand::Rng;
@@ -11,7 +14,7 @@ use super::palette::Palette;
 use super::preset::Preset;
 use crate::lights::Lights;
 use crate::logic::{BeamPattern, PadBinding, PadOp};
-use crate::{DiscoBall, FixtureChannel, FixtureIndex};
+use crate::{DiscoBall, FixtureIndex};
 
 ///////////////////////// BINDINGS /////////////////////////
 
@@ -323,12 +326,34 @@ pub struct Beat {
 
 ///////////////////////// TICK /////////////////////////
 
-pub fn tick(mut s: ResMut<State>, mut syn: ResMut<Synesthesia>, time: Res<Time>) {
+/// How quickly the phase is pulled onto the MIDI clock's beat, per second.
+const CLOCK_PULL: f32 = 2.0;
+
+pub fn tick(
+    mut s: ResMut<State>,
+    mut syn: ResMut<Synesthesia>,
+    clock: Res<ClockSync>,
+    clock_out: Option<ResMut<ClockOut>>,
+    time: Res<Time>,
+) {
     let s: &mut State = &mut *s;
     let dt = time.delta_secs();
 
     s.t += dt;
+    // Slave to MIDI clock while it's playing, otherwise run at the tapped bpm.
+    let slave = clock.bpm().zip(clock.beat(Instant::now()));
+    if let Some((bpm, _)) = slave {
+        s.bpm = bpm;
+    }
     s.phi += (s.bpm / 60.0) * s.phi_mul * dt;
+    // Pull the phase onto the clock's beat gradually, so it doesn't jump when the clock takes over.
+    if let Some((_, beat)) = slave {
+        let err = (beat * s.phi_mul - s.phi + 0.5).rem_euclid(1.0) - 0.5;
+        s.phi += err * (1.0 - (-CLOCK_PULL * dt).exp());
+    }
+    if let Some(mut clock_out) = clock_out {
+        clock_out.set_bpm(s.bpm);
+    }
 
     {
         let g: RgbGradient = s.preset.visuals_gradient(s).into();
@@ -406,12 +431,11 @@ pub fn tick(mut s: ResMut<State>, mut syn: ResMut<Synesthesia>, time: Res<Time>)
 ///////////////////////// LIGHTS /////////////////////////
 
 pub fn render_lights<'a>(
-    beams: Query<(&'a mut StealthBeam, &'a Transform, &'a FixtureChannel, &'a FixtureIndex)>,
-    spots: Query<(&'a mut SaberSpot, &'a Transform, &'a FixtureChannel, &'a FixtureIndex)>,
+    beams: Query<(&'a mut StealthBeam, &'a Transform, &'a FixtureIndex)>,
+    spots: Query<(&'a mut SaberSpot, &'a Transform, &'a FixtureIndex)>,
     disco: Query<&'a Transform, With<DiscoBall>>,
 
     mut s: ResMut<State>,
-    mut e131: ResMut<E131>,
 ) {
     let s: &mut State = &mut *s;
     let Some(mut l) = Lights::new(beams, spots, disco) else {
@@ -443,18 +467,21 @@ pub fn render_lights<'a>(
         l.for_each_beam(|beam, _, _| beam.color = beam.color * s.beat_fr0().unwrap_or(1.0));
         l.for_each_spot(|par, _, _| par.color = par.color * s.beat_fr1().unwrap_or(1.0));
     }
-    l.send(&mut *e131);
 }
 
 ///////////////////////// PAD INPUT /////////////////////////
 
-pub fn on_pad(mut s: ResMut<State>, mut pad: ResMut<Midi<LaunchpadX>>) {
+pub fn on_pad(
+    mut s: ResMut<State>,
+    mut inputs: EventReader<MidiInput<LaunchpadX>>,
+    mut pad: ResMut<Midi<LaunchpadX>>,
+) {
     let s: &mut State = &mut *s;
     let pad: &mut Midi<LaunchpadX> = &mut *pad;
 
     use launchpad_x::*;
 
-    for input in pad.recv() {
+    for input in inputs.read().map(|e| e.input) {
         // Handle shift keys
         match input {
             Input::Up(b) => s.shift[0] = b,
@@ -671,10 +698,10 @@ pub fn render_pad(mut s: ResMut<State>, mut pad: ResMut<Midi<LaunchpadX>>) {
 
 ///////////////////////// CTRL INPUT /////////////////////////
 
-pub fn on_ctrl(mut s: ResMut<State>, mut ctrl: ResMut<Midi<LaunchControlXL>>) {
+pub fn on_ctrl(mut s: ResMut<State>, mut ctrl: EventReader<MidiInput<LaunchControlXL>>) {
     let s: &mut State = &mut *s;
 
-    for input in ctrl.recv() {
+    for input in ctrl.read().map(|e| e.input) {
         use launch_control_xl::*;
         debug!("ctrl: {input:?}");
 
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::{Mutex, mpsc};
+
+use anyhow::{Context, Result};
+
+use crate::dmx::UNIVERSE_SIZE;
+use crate::prelude::*;
+
+/// The Art-Net UDP port.
+pub(crate) const PORT: u16 = 6454;
+
+/// Packet header shared by every Art-Net opcode.
+const ID: &[u8; 8] = b"Art-Net\0";
+/// Art-Net 4 protocol revision.
+const PROTOCOL_VERSION: u16 = 14;
+
+const OP_POLL: u16 = 0x2000;
+const OP_POLL_REPLY: u16 = 0x2100;
+const OP_DMX: u16 = 0x5000;
+
+/// Art-Net sender.
+///
+/// # Protocol
+///
+/// Art-Net is a protocol for sending DMX over UDP, either broadcast or unicast
+/// to individual nodes. It predates E1.31 and lots of cheaper nodes only speak it.
+///
+/// Art-Net port addresses are 0-indexed, so universe `n` is sent to port address `n - 1`
+/// to line up with E1.31 numbering.
+///
+/// See <https://art-net.org.uk/how-it-works/>
+#[derive(Resource)]
+pub struct ArtNet {
+    sock: UdpSocket,
+    dest: SocketAddr,
+    /// Last ArtDmx sequence number sent per universe.
+    sequence: HashMap<u16, u8>,
+
+    nodes_rx: Mutex<mpsc::Receiver<ArtNetNode>>,
+    nodes: Vec<ArtNetNode>,
+}
+
+/// A node which replied to an ArtPoll.
+#[derive(Clone, Debug)]
+pub struct ArtNetNode {
+    pub ip: Ipv4Addr,
+    pub short_name: String,
+    pub long_name: String,
+}
+
+impl ArtNet {
+    /// Constructs a new Art-Net sender. `dest_ip` can be a node or a broadcast address.
+    pub fn new(dest_ip: &str) -> Result<Self> {
+        let dest: IpAddr = dest_ip.parse().with_context(|| format!("failed to parse ip: {dest_ip:?}"))?;
+        let dest = SocketAddr::new(dest, PORT);
+
+        // Nodes reply to ArtPoll on the Art-Net port, so try to bind it first.
+        let sock = match UdpSocket::bind(("0.0.0.0", PORT)) {
+            Ok(sock) => sock,
+            Err(e) => {
+                warn!("Failed to bind Art-Net port {PORT}, ArtPoll replies won't be received: {e}");
+                UdpSocket::bind("0.0.0.0:0").context("Failed to bind Art-Net socket")?
+            }
+        };
+        Self::with_socket(sock, dest)
+    }
+
+    /// Constructs a new Art-Net sender on an already bound socket, sending to `dest`.
+    ///
+    /// ArtPoll replies come back to whatever address `sock` is bound to.
+    pub fn with_socket(sock: UdpSocket, dest: SocketAddr) -> Result<Self> {
+        sock.set_broadcast(true)?;
+
+        // Spawn a worker thread which collects ArtPollReplies.
+        let (tx, rx) = mpsc::channel();
+        let recv_sock = sock.try_clone()?;
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 1024];
+            loop {
+                let size = match recv_sock.recv_from(&mut buf) {
+                    Ok((size, _addr)) => size,
+                    // Windows reports ICMP port unreachable from an earlier send here.
+                    Err(e) if matches!(e.kind(), ErrorKind::Interrupted | ErrorKind::ConnectionReset) => {
+                        continue;
+                    }
+                    // Anything else won't go away by retrying, e.g. the socket was closed.
+                    Err(e) => {
+                        error!("Failed to receive ArtPoll replies, giving up: {e}");
+                        return;
+                    }
+                };
+
+                if let Some(node) = parse_poll_reply(&buf[..size]) {
+                    // The main thread exited and we're shutting down anyways.
+                    if tx.send(node).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            sock,
+            dest,
+            sequence: HashMap::new(),
+            nodes_rx: Mutex::new(rx),
+            nodes: vec![],
+        })
+    }
+
+    /// Send an ArtPoll to discover nodes. Replies show up in `nodes()`.
+    pub fn poll(&mut self) {
+        let dest = self.dest;
+        if let Err(e) = self.sock.send_to(&encode_poll(), dest) {
+            error!("Failed to send ArtPoll to {dest}: {e}");
+        }
+    }
+
+    /// Nodes which have replied to an ArtPoll so far.
+    pub fn nodes(&mut self) -> &[ArtNetNode] {
+        while let Ok(node) = self.nodes_rx.lock().unwrap().try_recv() {
+            match self.nodes.iter_mut().find(|n| n.ip == node.ip) {
+                Some(existing) => *existing = node,
+                None => self.nodes.push(node),
+            }
+        }
+        &self.nodes
+    }
+}
+
+impl DmxOutput for ArtNet {
+    fn send_universe(&mut self, universe: u16, payload: &[u8]) {
+        assert!(payload.len() <= UNIVERSE_SIZE);
+
+        // ArtDmx only carries the null start code.
+        let Some((&0, data)) = payload.split_first() else {
+            return;
+        };
+
+        // Sequence numbers run from 1..=255, 0 disables reordering on the node.
+        let sequence = self.sequence.entry(universe).or_insert(0);
+        *sequence = sequence.checked_add(1).unwrap_or(1);
+
+        let dest = self.dest;
+        let packet = encode_dmx(universe, *sequence, data);
+        if let Err(e) = self.sock.send_to(&packet, dest) {
+            error!("Failed to send Art-Net universe {universe} to {dest}: {e}");
+        }
+    }
+}
+
+/// Encode an ArtDmx packet for the given 1-indexed universe. `data` excludes the start code.
+fn encode_dmx(universe: u16, sequence: u8, data: &[u8]) -> Vec<u8> {
+    let port_address = universe.saturating_sub(1) & 0x7FFF;
+    // Length must be even and at least 2.
+    let len = (data.len().max(2) + 1) & !1;
+
+    let mut packet = Vec::with_capacity(18 + len);
+    packet.extend_from_slice(ID);
+    packet.extend_from_slice(&OP_DMX.to_le_bytes());
+    packet.extend_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+    packet.push(sequence);
+    packet.push(0); // physical input port
+    packet.extend_from_slice(&port_address.to_le_bytes()); // SubUni, Net
+    packet.extend_from_slice(&(len as u16).to_be_bytes());
+    packet.extend_from_slice(data);
+    packet.resize(18 + len, 0);
+    packet
+}
+
+/// Encode an ArtPoll packet.
+fn encode_poll() -> Vec<u8> {
+    let mut packet = Vec::with_capacity(14);
+    packet.extend_from_slice(ID);
+    packet.extend_from_slice(&OP_POLL.to_le_bytes());
+    packet.extend_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+    packet.push(0); // flags
+    packet.push(0); // diagnostics priority
+    packet
+}
+
+/// Get the opcode of an Art-Net packet, if it is one.
+fn opcode(packet: &[u8]) -> Option<u16> {
+    if packet.len() < 10 || &packet[..8] != ID {
+        return None;
+    }
+    Some(u16::from_le_bytes([packet[8], packet[9]]))
+}
+
+/// Parse an ArtPollReply packet.
+fn parse_poll_reply(packet: &[u8]) -> Option<ArtNetNode> {
+    if opcode(packet)? != OP_POLL_REPLY || packet.len() < 108 {
+        return None;
+    }
+
+    let ip = Ipv4Addr::new(packet[10], packet[11], packet[12], packet[13]);
+    let string = |bytes: &[u8]| {
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..end]).into_owned()
+    };
+
+    Some(ArtNetNode {
+        ip,
+        short_name: string(&packet[26..44]),
+        long_name: string(&packet[44..108]),
+    })
+}
+
+/// Parse an ArtDmx packet into its 1-indexed universe and channel data, excluding the start code.
+pub(crate) fn parse_dmx(packet: &[u8]) -> Option<(u16, &[u8])> {
+    if opcode(packet)? != OP_DMX || packet.len() < 18 {
+        return None;
+    }
+
+    let port_address = u16::from_le_bytes([packet[14], packet[15]]) & 0x7FFF;
+    let len = u16::from_be_bytes([packet[16], packet[17]]) as usize;
+    let data = &packet[18..(18 + len).min(packet.len())];
+
+    Some((port_address + 1, data))
+}
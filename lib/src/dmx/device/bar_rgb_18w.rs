@@ -2,11 +2,13 @@
 //!
 //! https://www.amazon.com/gp/product/B0045EP4WG
 
+use bevy::prelude::Component;
+
 use crate::color::Rgbw;
 use crate::dmx::DmxDevice;
-use crate::math::Interp;
+use crate::math::{Byte, Interp};
 
-#[derive(Default, Clone, Copy, Debug)]
+#[derive(Default, Clone, Copy, Debug, Component)]
 pub struct Bar {
     pub color: Rgbw,
     pub alpha: f32,
@@ -39,6 +41,11 @@ impl DmxDevice for Bar {
         // buf[5]: mode
         buf[6] = self.alpha.byte();
     }
+
+    fn decode(&mut self, buf: &[u8]) {
+        self.color = Rgbw(buf[0].float(), buf[1].float(), buf[2].float(), 0.0);
+        self.alpha = buf[6].float();
+    }
 }
 
 // #[derive(Default, Clone, Copy, Debug)]
@@ -2,11 +2,13 @@
 //!
 //! TODO: amazon link
 
+use bevy::prelude::Component;
+
 use crate::color::Rgbw;
-use crate::dmx::DmxDevice;
-use crate::math::Interp;
+use crate::dmx::{DmxDevice, Shutter};
+use crate::math::{Byte, Interp};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Component)]
 pub struct Beam {
     pub mode: BeamMode,
     pub ring: BeamRing,
@@ -17,6 +19,8 @@ pub struct Beam {
 
     pub color: Rgbw,
     pub alpha: f32,
+    /// Only open and closed for now, see `encode()`.
+    pub shutter: Shutter,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -63,15 +67,17 @@ impl DmxDevice for Beam {
     fn encode(&self, buf: &mut [u8]) {
         let Rgbw(r, g, b, w) = self.color;
 
-        buf[0] = self.yaw.byte();
+        [buf[0], buf[1]] = self.yaw.word().to_be_bytes();
         // buf[0] = (self.yaw * (2.0 / 3.0)).byte();
         // buf[0] = self.yaw.lerp((1.0 / 3.0)..1.0).byte();
-        // buf[1]: yaw fine
-        buf[2] = self.pitch.byte();
-        // buf[3]: pitch fine
+        [buf[2], buf[3]] = self.pitch.word().to_be_bytes();
         buf[4] = (1.0 - self.speed).byte();
-        buf[5] = self.alpha.byte();
-        // buf[6]: strobe
+        // Close the shutter with the dimmer, since strobe isn't sent.
+        buf[5] = match self.shutter {
+            Shutter::Closed => 0,
+            _ => self.alpha.byte(),
+        };
+        // buf[6]: strobe, TODO: its range isn't known yet, so strobing leaves the shutter open.
         buf[7] = r.byte();
         buf[8] = g.byte();
         buf[9] = b.byte();
@@ -81,6 +87,21 @@ impl DmxDevice for Beam {
         // buf[13]: auto pitch/yaw, reset
         buf[14] = self.ring.byte();
     }
+
+    fn decode(&mut self, buf: &[u8]) {
+        self.yaw = u16::from_be_bytes([buf[0], buf[1]]).float();
+        self.pitch = u16::from_be_bytes([buf[2], buf[3]]).float();
+        self.speed = 1.0 - buf[4].float();
+        self.alpha = buf[5].float();
+        self.shutter = Shutter::Open;
+        self.color = Rgbw(buf[7].float(), buf[8].float(), buf[9].float(), buf[10].float());
+        self.mode = match buf[12] {
+            159 => BeamMode::ColorCycle,
+            60 => BeamMode::Auto,
+            _ => BeamMode::Manual,
+        };
+        self.ring = BeamRing::Raw(buf[14]);
+    }
 }
 
 impl BeamRing {
@@ -136,6 +157,7 @@ impl Default for Beam {
 
             color: Rgbw::BLACK,
             alpha: 1.0,
+            shutter: Shutter::Open,
         }
     }
 }
@@ -3,11 +3,13 @@
 //! https://www.amazon.com/gp/product/B089QGPJ2L
 //! https://www.aliexpress.com/w/wholesale-Beam-60W-LED-Moving-Head-RGBW-4-IN-1-Stage-Lightin.html
 
+use bevy::prelude::Component;
+
 use crate::color::Rgbw;
 use crate::dmx::DmxDevice;
 use crate::math::Interp;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Component)]
 pub struct BigBeam {
     pub pitch: f32,
     pub yaw: f32,
@@ -25,6 +27,8 @@ impl DmxDevice for BigBeam {
     fn encode(&self, buf: &mut [u8]) {
         let Rgbw(r, g, b, w) = self.color;
 
+        // This 13 channel mode has no fine pan/tilt, they're coarse only on 0 and 1.
+        // The channels after white are left at 0.
         buf[0] = self.yaw.byte();
         // buf[0] = (self.yaw * (2.0 / 3.0)).byte();
         // buf[0] = self.yaw.lerp((1.0 / 3.0)..1.0).byte();
@@ -2,10 +2,9 @@
 //!
 //! <TODO: amazon link>
 
-use crate::dmx::DmxDevice;
-use crate::math::Interp;
+use crate::prelude::*;
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Component)]
 pub struct Gobo {
     pub pan: f32,
     pub tilt: f32,
@@ -33,4 +32,15 @@ impl DmxDevice for Gobo {
         buf[7] = self.auto.byte();
         buf[8] = 0; // reset
     }
+
+    fn decode(&mut self, buf: &[u8]) {
+        self.pan = buf[0].float();
+        self.tilt = buf[1].float();
+        self.color = buf[2].float();
+        self.pattern = buf[3].float();
+        self.strobe = buf[4].float();
+        self.alpha = buf[5].float();
+        self.speed = buf[6].float();
+        self.auto = buf[7].float();
+    }
 }
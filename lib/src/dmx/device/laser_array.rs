@@ -2,11 +2,13 @@
 //!
 //! https://www.amazon.com/gp/product/B09LVGQ2GY
 
+use bevy::prelude::Component;
+
 use crate::color::Rgb;
 use crate::dmx::DmxDevice;
 use crate::math::Interp;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Component)]
 pub struct LaserArray {
     pub angle: f32,
     pub brightness: f32,
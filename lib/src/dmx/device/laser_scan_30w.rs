@@ -2,11 +2,13 @@
 //!
 //! https://www.amazon.com/gp/product/B09LVGQ2GY
 
+use bevy::prelude::Component;
+
 use crate::color::Rgb;
 use crate::dmx::DmxDevice;
 use crate::math::Interp;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Component)]
 pub struct Laser {
     pub on: bool,
 
@@ -128,6 +130,34 @@ impl LaserColor {
         }
     }
 
+    /// Parse a raw value, falling back to `Raw` if it isn't one of the pure colors.
+    pub fn from_byte(byte: u8) -> Self {
+        let rgb = [(true, false, false), (false, true, false), (false, false, true)];
+        let mixed = [
+            (true, true, false),
+            (true, false, true),
+            (false, true, true),
+            (true, true, true),
+        ];
+        rgb.into_iter()
+            .chain(mixed)
+            .map(|(r, g, b)| LaserColor::Rgb(r, g, b))
+            .find(|color| color.byte() == byte)
+            .unwrap_or(LaserColor::Raw(byte))
+    }
+
+    /// Approximate color of the beam, white for the color cycling modes.
+    pub fn rgb(self) -> Rgb {
+        match self {
+            LaserColor::Rgb(r, g, b) => Rgb(r as u8 as f32, g as u8 as f32, b as u8 as f32),
+            LaserColor::Raw(i) => match LaserColor::from_byte(i) {
+                LaserColor::Raw(_) => Rgb::WHITE,
+                color => color.rgb(),
+            },
+            LaserColor::Mix(_) => Rgb::WHITE,
+        }
+    }
+
     pub fn from_rgb(rgb: Rgb) -> Self {
         match rgb {
             Rgb::RED => Self::RED,
@@ -145,9 +175,78 @@ impl LaserStroke {
             LaserStroke::Dots(fr) => fr.inv().lerp(128..255) as u8,
         }
     }
+
+    pub fn from_byte(byte: u8) -> Self {
+        match byte {
+            0..128 => LaserStroke::Solid(1.0 - byte as f32 / 127.0),
+            _ => LaserStroke::Dots(1.0 - (byte - 128) as f32 / 127.0),
+        }
+    }
 }
 
 impl LaserPattern {
+    /// Every named pattern.
+    pub const ALL: [LaserPattern; 50] = [
+        LaserPattern::Square,
+        LaserPattern::SquareWide,
+        LaserPattern::SquareXWide,
+        LaserPattern::SquareBlock,
+        LaserPattern::Circle,
+        LaserPattern::CircleWide,
+        LaserPattern::CircleDash,
+        LaserPattern::CircleQuad,
+        LaserPattern::CircleCircle,
+        LaserPattern::CircleSquare,
+        LaserPattern::CircleX,
+        LaserPattern::CircleY,
+        LaserPattern::LineX,
+        LaserPattern::LineY,
+        LaserPattern::LineXY,
+        LaserPattern::LineDX,
+        LaserPattern::LineDY,
+        LaserPattern::Line2X,
+        LaserPattern::Line2Y,
+        LaserPattern::LinePenta,
+        LaserPattern::LineStair,
+        LaserPattern::Tri,
+        LaserPattern::TriX,
+        LaserPattern::TriY,
+        LaserPattern::Tri3d,
+        LaserPattern::TriTri,
+        LaserPattern::TriCircle,
+        LaserPattern::TriWing,
+        LaserPattern::TriArch,
+        LaserPattern::Penta,
+        LaserPattern::Squig1,
+        LaserPattern::Squig2,
+        LaserPattern::Three,
+        LaserPattern::Two,
+        LaserPattern::One,
+        LaserPattern::Music,
+        LaserPattern::Tree,
+        LaserPattern::Star,
+        LaserPattern::Sin,
+        LaserPattern::Heart,
+        LaserPattern::Elephant,
+        LaserPattern::Apple,
+        LaserPattern::Plus,
+        LaserPattern::PlusOval,
+        LaserPattern::PlusArrow,
+        LaserPattern::PlusDia,
+        LaserPattern::Arrow,
+        LaserPattern::ArrowInvert,
+        LaserPattern::Hourglass1,
+        LaserPattern::Hourglass2,
+    ];
+
+    /// Parse a raw value, falling back to `Raw` if it isn't one of the named patterns.
+    pub fn from_byte(byte: u8) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.byte() == byte)
+            .unwrap_or(LaserPattern::Raw(byte))
+    }
+
     pub fn byte(self) -> u8 {
         match self {
             LaserPattern::Raw(i) => i,
@@ -241,4 +340,17 @@ impl DmxDevice for Laser {
         buf[8] = self.color.byte();
         buf[9] = self.stroke.byte();
     }
+
+    fn decode(&mut self, buf: &[u8]) {
+        self.on = buf[0] >= 64;
+        self.pattern = LaserPattern::from_byte(buf[1]);
+        self.rotate = buf[2] as f32 / 127.0;
+        self.yflip = buf[3] as f32 / 127.0;
+        self.xflip = buf[4] as f32 / 127.0;
+        self.x = buf[5] as f32 / 127.0;
+        self.y = buf[6] as f32 / 127.0;
+        self.size = buf[7] as f32 / 63.0;
+        self.color = LaserColor::from_byte(buf[8]);
+        self.stroke = LaserStroke::from_byte(buf[9]);
+    }
 }
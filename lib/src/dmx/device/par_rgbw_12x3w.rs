@@ -2,11 +2,13 @@
 //!
 //! https://www.aliexpress.com/w/wholesale-12x3w-rgbw-dmx-led-par-light.html
 
+use bevy::prelude::Component;
+
 use crate::color::Rgbw;
 use crate::dmx::DmxDevice;
-use crate::math::Interp;
+use crate::math::{Byte, Interp};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Component)]
 pub struct Par {
     pub color: Rgbw,
 }
@@ -28,6 +30,10 @@ impl DmxDevice for Par {
         buf[6] = b.byte();
         buf[7] = w.byte();
     }
+
+    fn decode(&mut self, buf: &[u8]) {
+        self.color = Rgbw(buf[4].float(), buf[5].float(), buf[6].float(), buf[7].float());
+    }
 }
 
 impl Default for Par {
@@ -2,11 +2,13 @@
 //!
 //! https://www.amazon.com/gp/product/B081H833BG
 
+use bevy::prelude::Component;
+
 use crate::color::Rgbw;
 use crate::dmx::DmxDevice;
 use crate::math::Interp;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Component)]
 pub struct Spider {
     // pub mode: SpiderMode,
     // pub speed: f32,
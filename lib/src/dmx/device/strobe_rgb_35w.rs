@@ -2,11 +2,13 @@
 //!
 //! https://www.amazon.com/gp/product/B01MZYQJSA
 
+use bevy::prelude::Component;
+
 use crate::color::Rgb;
 use crate::dmx::DmxDevice;
-use crate::math::Interp;
+use crate::math::{Byte, Interp};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Component)]
 pub struct Strobe {
     // pub mode: StrobeMode,
     pub color: Rgb,
@@ -28,6 +30,11 @@ impl DmxDevice for Strobe {
         buf[4] = b.byte();
         // buf[5]: sound control
     }
+
+    fn decode(&mut self, buf: &[u8]) {
+        self.alpha = buf[0].float();
+        self.color = Rgb(buf[2].float(), buf[3].float(), buf[4].float());
+    }
 }
 
 // pub enum StrobeMode {
@@ -0,0 +1,79 @@
+//! Golden frame tests for `DmxDevice::encode` and `decode`.
+//!
+//! A wrong byte offset only shows up at a venue, so every device gets a test
+//! rendering a few known states and comparing against the expected bytes:
+//!
+//! ```ignore
+//! let beam = StealthBeam { yaw: 0.5, ..Default::default() };
+//! assert_encodes(&beam, &[0x7F, 0xFF, ...]);
+//! ```
+
+use std::fmt::Write;
+
+use crate::dmx::DmxDevice;
+
+/// Bytes past `channels()` which are checked for stray writes.
+const GUARD: usize = 16;
+
+/// Encode a device into a zeroed buffer of exactly `channels()` bytes, the same
+/// as when it's patched.
+///
+/// Panics if `encode()` writes past `channels()`.
+pub fn render(device: &dyn DmxDevice) -> Vec<u8> {
+    let channels = device.channels();
+
+    // Run twice with different fill bytes, so writing the fill value doesn't go unnoticed.
+    for fill in [0x00, 0xFF] {
+        let mut buf = vec![fill; channels + GUARD];
+        device.encode(&mut buf[..channels]);
+        assert_untouched(&buf[channels..], fill, channels);
+
+        // Devices also shouldn't look past their slice, e.g. with `buf.len()`.
+        let mut buf = vec![fill; channels + GUARD];
+        device.encode(&mut buf);
+        assert_untouched(&buf[channels..], fill, channels);
+    }
+
+    let mut buf = vec![0; channels];
+    device.encode(&mut buf);
+    buf
+}
+
+/// Encode a device and compare it against the expected bytes, with a
+/// per-channel diff on mismatch.
+#[track_caller]
+pub fn assert_encodes(device: &dyn DmxDevice, expected: &[u8]) {
+    let actual = render(device);
+    if actual == expected {
+        return;
+    }
+
+    let mut diff = String::new();
+    if actual.len() != expected.len() {
+        writeln!(diff, "  expected {} channels, got {}", expected.len(), actual.len()).unwrap();
+    }
+    for i in 0..actual.len().max(expected.len()) {
+        let (e, a) = (expected.get(i), actual.get(i));
+        if e != a {
+            writeln!(diff, "  buf[{i}] (ch {}): expected {e:?}, got {a:?}", i + 1).unwrap();
+        }
+    }
+    panic!("encoded frame doesn't match\n  expected: {expected:?}\n    actual: {actual:?}\n{diff}");
+}
+
+/// Encode a device, decode the frame into a default device and encode that
+/// again, checking nothing was lost along the way.
+#[track_caller]
+pub fn assert_round_trips<D: DmxDevice + Default>(device: &D) {
+    let frame = render(device);
+    let mut decoded = D::default();
+    decoded.decode(&frame);
+    assert_encodes(&decoded, &frame);
+}
+
+#[track_caller]
+fn assert_untouched(guard: &[u8], fill: u8, channels: usize) {
+    if let Some(i) = guard.iter().position(|&b| b != fill) {
+        panic!("encode() wrote buf[{}], past its {channels} channels", channels + i);
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+
+use crate::dmx::{DmxOutput, UNIVERSE_SIZE};
+use crate::prelude::*;
+
+/// The default source priority, same as E1.31.
+const DEFAULT_PRIORITY: u8 = 100;
+
+/// How a channel is merged between sources of equal priority.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergeMode {
+    /// Highest takes precedence, e.g. for dimmers.
+    #[default]
+    Htp,
+    /// Latest takes precedence, e.g. for pan/tilt and color mixing.
+    Ltp,
+}
+
+/// DMX merge engine.
+///
+/// Several named sources write into their own layer, e.g. a manual override
+/// fader page and a preset engine. Per channel, only the highest priority
+/// sources which have written that channel are considered, and between those
+/// the channel's `MergeMode` picks the final value.
+#[derive(Resource, Default)]
+pub struct DmxMerge {
+    sources: Vec<Source>,
+    /// Per-universe channel modes, HTP if unset.
+    modes: HashMap<u16, Box<[MergeMode; UNIVERSE_SIZE]>>,
+    /// Monotonic counter used to order LTP changes.
+    clock: u64,
+}
+
+struct Source {
+    name: String,
+    priority: u8,
+    layers: BTreeMap<u16, Layer>,
+}
+
+/// One source's contribution to a universe.
+struct Layer {
+    values: [u8; UNIVERSE_SIZE],
+    /// When each channel last changed, or 0 if it hasn't been written.
+    changed: [u64; UNIVERSE_SIZE],
+}
+
+/// Handle for writing into a source's layers, from `DmxMerge::source()`.
+pub struct DmxSource<'a> {
+    source: &'a mut Source,
+    clock: &'a mut u64,
+}
+
+impl DmxMerge {
+    /// Get a source by name, creating it with `DEFAULT_PRIORITY` if it doesn't exist.
+    pub fn source(&mut self, name: &str) -> DmxSource<'_> {
+        let i = match self.sources.iter().position(|s| s.name == name) {
+            Some(i) => i,
+            None => {
+                self.sources.push(Source {
+                    name: name.to_string(),
+                    priority: DEFAULT_PRIORITY,
+                    layers: BTreeMap::new(),
+                });
+                self.sources.len() - 1
+            }
+        };
+        DmxSource { source: &mut self.sources[i], clock: &mut self.clock }
+    }
+
+    /// Remove a source and release all of its channels.
+    pub fn remove(&mut self, name: &str) {
+        self.sources.retain(|s| s.name != name);
+    }
+
+    /// Set the merge mode for a range of 1-indexed channels.
+    pub fn set_mode(&mut self, universe: u16, channels: Range<usize>, mode: MergeMode) {
+        let modes = self
+            .modes
+            .entry(universe)
+            .or_insert_with(|| Box::new([MergeMode::Htp; UNIVERSE_SIZE]));
+        modes[clamp(channels)].fill(mode);
+    }
+
+    /// Universes written by any source, in ascending order.
+    pub fn universes(&self) -> Vec<u16> {
+        let mut universes = self.sources.iter().flat_map(|s| s.layers.keys().copied()).collect::<Vec<_>>();
+        universes.sort_unstable();
+        universes.dedup();
+        universes
+    }
+
+    /// Merge all sources into the final frame for a universe. Unwritten channels are 0.
+    pub fn frame(&self, universe: u16) -> [u8; UNIVERSE_SIZE] {
+        let mut frame = [0u8; UNIVERSE_SIZE];
+        let modes = self.modes.get(&universe);
+        let layers = self
+            .sources
+            .iter()
+            .filter_map(|s| Some((s.priority, s.layers.get(&universe)?)))
+            .collect::<Vec<_>>();
+
+        // Skip the start code
+        for ch in 1..UNIVERSE_SIZE {
+            let mode = modes.map_or(MergeMode::Htp, |m| m[ch]);
+
+            // (priority, changed, value) of the winning layer so far
+            let mut winner: Option<(u8, u64, u8)> = None;
+            for &(priority, layer) in &layers {
+                let (changed, value) = (layer.changed[ch], layer.values[ch]);
+                if changed == 0 {
+                    continue;
+                }
+
+                let wins = match winner {
+                    None => true,
+                    Some((p, _, _)) if priority != p => priority > p,
+                    Some((_, c, v)) => match mode {
+                        MergeMode::Htp => value > v,
+                        MergeMode::Ltp => changed > c,
+                    },
+                };
+                if wins {
+                    winner = Some((priority, changed, value));
+                }
+            }
+
+            if let Some((_, _, value)) = winner {
+                frame[ch] = value;
+            }
+        }
+
+        frame
+    }
+
+    /// Merge and send every universe written by any source.
+    pub fn send(&self, out: &mut dyn DmxOutput) {
+        for universe in self.universes() {
+            out.send_universe(universe, &self.frame(universe));
+        }
+        out.flush();
+    }
+}
+
+impl DmxSource<'_> {
+    /// Set the priority from 0..=200, higher wins regardless of merge mode.
+    pub fn set_priority(&mut self, priority: u8) -> &mut Self {
+        self.source.priority = priority.min(200);
+        self
+    }
+
+    /// Write a single 1-indexed channel.
+    pub fn set(&mut self, universe: u16, channel: usize, value: u8) -> &mut Self {
+        self.write(universe, channel, &[value])
+    }
+
+    /// Write a run of values starting at a 1-indexed channel.
+    pub fn write(&mut self, universe: u16, start: usize, values: &[u8]) -> &mut Self {
+        *self.clock += 1;
+        let now = *self.clock;
+
+        let layer = self
+            .source
+            .layers
+            .entry(universe)
+            .or_insert_with(|| Layer { values: [0; UNIVERSE_SIZE], changed: [0; UNIVERSE_SIZE] });
+
+        let end = (start + values.len()).min(UNIVERSE_SIZE);
+        for (ch, &value) in (start..end).zip(values) {
+            // Only bump the timestamp on change, so resending the same value doesn't steal LTP channels.
+            if layer.changed[ch] == 0 || layer.values[ch] != value {
+                layer.values[ch] = value;
+                layer.changed[ch] = now;
+            }
+        }
+        self
+    }
+
+    /// Stop contributing to a range of 1-indexed channels.
+    pub fn release(&mut self, universe: u16, channels: Range<usize>) -> &mut Self {
+        if let Some(layer) = self.source.layers.get_mut(&universe) {
+            layer.changed[clamp(channels)].fill(0);
+        }
+        self
+    }
+
+    /// Stop contributing to any channels.
+    pub fn clear(&mut self) -> &mut Self {
+        self.source.layers.clear();
+        self
+    }
+}
+
+/// Writes whole frames into the source, so any `DmxUniverse` can render into it.
+impl DmxOutput for DmxSource<'_> {
+    fn send_universe(&mut self, universe: u16, payload: &[u8]) {
+        // Skip the start code
+        if let Some(channels) = payload.get(1..) {
+            self.write(universe, 1, channels);
+        }
+    }
+}
+
+/// Clamp a range of channels to the universe. Like `DmxSource::write()`, channels past the end are ignored.
+fn clamp(channels: Range<usize>) -> Range<usize> {
+    let end = channels.end.min(UNIVERSE_SIZE);
+    channels.start.min(end)..end
+}
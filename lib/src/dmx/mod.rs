@@ -1,12 +1,138 @@
-use crate::e131::E131;
+use crate::prelude::*;
 
 pub mod device;
+pub mod golden;
 
+mod merge;
+pub use merge::{DmxMerge, DmxSource, MergeMode};
+
+mod ofl;
+mod patch;
+pub use patch::{DmxAddress, PATCH_SOURCE, Patch};
+
+pub mod rdm;
+
+mod profile;
+pub use profile::{ChannelProfile, FixtureProfile, ProfileFixture, attr};
+
+mod record;
+pub use record::{DmxPlayer, DmxRecord, DmxRecorder};
+
+mod receiver;
+pub use receiver::{DmxInputFrame, DmxProtocol, DmxReceiver};
+
+mod scheduler;
+pub use scheduler::{DmxScheduler, DmxSchedulerConfig};
+
+mod shutter;
+pub use shutter::{NOMINAL_STROBE_HZ, Shutter};
+
+mod visualize;
+pub use visualize::DmxVisualizer;
+
+pub struct DmxPlugin;
+impl Plugin for DmxPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_component_as::<dyn DmxDevice, ProfileFixture>()
+            .register_component_as::<dyn DmxDevice, device::bar_rgb_18w::Bar>()
+            .register_component_as::<dyn DmxDevice, device::beam_rgbw_60w::Beam>()
+            .register_component_as::<dyn DmxDevice, device::beam_rgbw_90w::BigBeam>()
+            .register_component_as::<dyn DmxDevice, device::gobo_60w::Gobo>()
+            .register_component_as::<dyn DmxDevice, device::laser_array::LaserArray>()
+            .register_component_as::<dyn DmxDevice, device::laser_scan_30w::Laser>()
+            .register_component_as::<dyn DmxDevice, device::par_rgbw_12x3w::Par>()
+            .register_component_as::<dyn DmxDevice, device::spider_rgbw_8x10w::Spider>()
+            .register_component_as::<dyn DmxDevice, device::strobe_rgb_35w::Strobe>();
+
+        app.init_resource::<DmxMerge>()
+            .init_resource::<Patch>()
+            .add_systems(PreUpdate, receiver::update.run_if(resource_exists::<DmxReceiver>))
+            .add_systems(
+                PreUpdate,
+                (visualize::receive, visualize::decode)
+                    .chain()
+                    .after(receiver::update)
+                    .in_set(DmxDecode)
+                    .run_if(resource_exists::<DmxVisualizer>),
+            )
+            .add_systems(PostUpdate, (patch::sync, patch::encode).chain().in_set(DmxEncode))
+            .add_systems(PostUpdate, patch::send.after(DmxEncode))
+            .add_systems(Last, (scheduler::stop_on_exit, crate::e131::terminate_on_exit));
+    }
+}
+
+/// Systems which decode fixtures from received DMX with `DmxVisualizer`, in `PreUpdate`.
+/// Anything reading fixture state in `PreUpdate`, like the sim, should run after this set.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DmxDecode;
+
+/// Systems which encode fixtures into `DmxMerge`, in `PostUpdate`.
+/// Anything else writing into `DmxMerge` should run before the frames are sent after this set.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DmxEncode;
+
+/// Size of a DMX frame: the start code followed by 512 channels.
+pub const UNIVERSE_SIZE: usize = 513;
+
+#[bevy_trait_query::queryable]
 pub trait DmxDevice {
     fn channels(&self) -> usize;
     fn encode(&self, buf: &mut [u8]);
+    /// Set the device's state from its channels, the inverse of `encode()`.
+    ///
+    /// Used to drive the sim from DMX with `DmxVisualizer`. Attributes without
+    /// a field to decode into are skipped, and by default nothing is decoded.
+    fn decode(&mut self, _buf: &[u8]) {}
 }
 
+/// A set of devices which spans one or more DMX universes.
 pub trait DmxUniverse {
-    fn send(&self, e131: &mut E131);
+    /// Encode the devices and send them, calling `DmxOutput::send_universe()` once per universe
+    /// followed by `DmxOutput::flush()`.
+    fn send(&self, out: &mut dyn DmxOutput);
+}
+
+/// A DMX output backend, e.g. `E131`, `ArtNet`, or `EnttecPro`.
+///
+/// Universes are 1-indexed as in E1.31, and `payload[0]` is the DMX start
+/// code, so channel `n` lives at `payload[n]`.
+pub trait DmxOutput {
+    /// Send a packet of up to 512 DMX channels on the given universe.
+    fn send_universe(&mut self, universe: u16, payload: &[u8]);
+
+    /// Called once all universes for a frame have been sent.
+    fn flush(&mut self) {}
+}
+
+impl<T: DmxOutput + ?Sized> DmxOutput for &mut T {
+    fn send_universe(&mut self, universe: u16, payload: &[u8]) {
+        (**self).send_universe(universe, payload);
+    }
+
+    fn flush(&mut self) {
+        (**self).flush();
+    }
+}
+
+impl<T: DmxOutput + ?Sized> DmxOutput for Box<T> {
+    fn send_universe(&mut self, universe: u16, payload: &[u8]) {
+        (**self).send_universe(universe, payload);
+    }
+
+    fn flush(&mut self) {
+        (**self).flush();
+    }
+}
+
+/// Send to both outputs, e.g. `(&mut *e131, &mut *artnet)`.
+impl<A: DmxOutput, B: DmxOutput> DmxOutput for (A, B) {
+    fn send_universe(&mut self, universe: u16, payload: &[u8]) {
+        self.0.send_universe(universe, payload);
+        self.1.send_universe(universe, payload);
+    }
+
+    fn flush(&mut self) {
+        self.0.flush();
+        self.1.flush();
+    }
 }
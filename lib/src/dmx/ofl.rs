@@ -0,0 +1,283 @@
+//! Open Fixture Library import.
+//!
+//! Converts one mode of an OFL fixture definition into a `FixtureProfile`,
+//! mapping the capabilities we know about onto the `attr` names used by the
+//! hand-written devices. Everything else is kept under its channel name with
+//! the raw DMX range.
+//!
+//! See <https://github.com/OpenLightingProject/open-fixture-library/blob/master/docs/fixture-format.md>
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::dmx::profile::{ChannelProfile, FixtureProfile, attr};
+use crate::prelude::*;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Fixture {
+    name: String,
+    #[serde(default)]
+    available_channels: BTreeMap<String, Channel>,
+    #[serde(default)]
+    wheels: BTreeMap<String, Wheel>,
+    modes: Vec<Mode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Channel {
+    #[serde(default)]
+    fine_channel_aliases: Vec<String>,
+    #[serde(default)]
+    default_value: Option<Value>,
+    #[serde(default)]
+    capability: Option<Capability>,
+    #[serde(default)]
+    capabilities: Vec<Capability>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Capability {
+    #[serde(rename = "type")]
+    ty: String,
+    #[serde(default)]
+    dmx_range: Option<(u16, u16)>,
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    shutter_effect: Option<String>,
+    #[serde(default)]
+    slot_number: Option<f32>,
+    #[serde(default)]
+    wheel: Option<String>,
+    #[serde(default)]
+    speed_start: Option<String>,
+    #[serde(default)]
+    comment: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Wheel {
+    slots: Vec<WheelSlot>,
+}
+
+#[derive(Deserialize)]
+struct WheelSlot {
+    #[serde(rename = "type")]
+    ty: String,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Mode {
+    name: String,
+    #[serde(default)]
+    short_name: Option<String>,
+    /// Channel keys or fine channel aliases, `null` for unused channels.
+    channels: Vec<Value>,
+}
+
+impl FixtureProfile {
+    /// Import a mode of an OFL fixture definition, or its first mode if `mode` is `None`.
+    ///
+    /// Modes can be selected by name or short name, e.g. `"16-channel"` or `"16ch"`.
+    pub fn from_ofl(json: &str, mode: Option<&str>) -> Result<Self> {
+        let fixture: Fixture = serde_json::from_str(json)?;
+
+        let selected = match mode {
+            Some(name) => {
+                let mut modes = fixture.modes.iter();
+                modes.find(|m| m.name == name || m.short_name.as_deref() == Some(name))
+            }
+            None => fixture.modes.first(),
+        };
+        let Some(mode) = selected else {
+            let modes = fixture.modes.iter().map(|m| &m.name).collect::<Vec<_>>();
+            bail!("{:?} has no mode {mode:?}, available modes are {modes:?}", fixture.name);
+        };
+
+        // Fine channels are referenced by alias, e.g. "Pan fine" for "Pan".
+        let aliases = fixture
+            .available_channels
+            .iter()
+            .flat_map(|(key, ch)| ch.fine_channel_aliases.first().map(|alias| (alias.as_str(), key.as_str())))
+            .collect::<HashMap<_, _>>();
+
+        let mut channels: Vec<ChannelProfile> = vec![];
+        let mut fine = HashMap::new();
+        for (offset, key) in mode.channels.iter().enumerate() {
+            let key = match key {
+                Value::Null => continue,
+                Value::String(key) => key.as_str(),
+                _ => bail!("{:?}: matrix channels aren't supported", fixture.name),
+            };
+
+            if let Some(&coarse) = aliases.get(key) {
+                fine.insert(coarse, offset);
+                continue;
+            }
+            let Some(channel) = fixture.available_channels.get(key) else {
+                warn!("{:?}: skipping unknown channel {key:?}", fixture.name);
+                continue;
+            };
+
+            let mut profile = import_channel(&fixture, key, channel, offset);
+            // Keep attributes unique, later duplicates fall back to their channel name.
+            if channels.iter().any(|ch| ch.attr == profile.attr) {
+                profile.attr = snake_case(key);
+            }
+            channels.push(profile);
+        }
+
+        for ch in &mut channels {
+            let key = mode.channels[ch.offset].as_str().unwrap_or_default();
+            ch.fine = fine.get(key).copied();
+            // OFL ranges are 8-bit unless stated otherwise, so scale them up for fine channels.
+            if ch.fine.is_some() {
+                ch.range = ch.range.map(|(lo, hi)| (lo << 8, (hi << 8) | 0xFF));
+            }
+        }
+
+        let profile = Self {
+            name: format!("{} ({})", fixture.name, mode.name),
+            footprint: Some(mode.channels.len()),
+            channels,
+        };
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Load a mode of an OFL fixture definition from a JSON file.
+    pub fn load_ofl(path: impl AsRef<Path>, mode: Option<&str>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read {path:?}"))?;
+        Self::from_ofl(&json, mode).with_context(|| format!("Failed to import OFL fixture {path:?}"))
+    }
+}
+
+/// Map an OFL channel onto a profile channel.
+fn import_channel(fixture: &Fixture, key: &str, channel: &Channel, offset: usize) -> ChannelProfile {
+    let caps = channel.capability.iter().chain(&channel.capabilities).collect::<Vec<_>>();
+
+    let mut profile = ChannelProfile {
+        attr: snake_case(key),
+        offset,
+        fine: None,
+        range: None,
+        invert: false,
+        default: default_value(channel.default_value.as_ref()),
+        slots: BTreeMap::new(),
+    };
+
+    // Channels with a single capability map 0.0..1.0 onto the whole range.
+    if let [cap] = caps[..] {
+        match cap.ty.as_str() {
+            "Intensity" => profile.attr = attr::ALPHA.into(),
+            "ColorIntensity" => {
+                if let Some(color) = &cap.color {
+                    profile.attr = match color.as_str() {
+                        "Red" => attr::RED.into(),
+                        "Green" => attr::GREEN.into(),
+                        "Blue" => attr::BLUE.into(),
+                        "White" => attr::WHITE.into(),
+                        color => snake_case(color),
+                    };
+                }
+            }
+            "Pan" => profile.attr = attr::YAW.into(),
+            "Tilt" => profile.attr = attr::PITCH.into(),
+            "PanTiltSpeed" => {
+                profile.attr = attr::SPEED.into();
+                profile.invert = cap.speed_start.as_deref() == Some("fast");
+            }
+            _ => {}
+        }
+        profile.range = cap.dmx_range;
+        return profile;
+    }
+
+    // Channels with several capabilities become named slots, plus a continuous range
+    // for the one we can drive with a float, e.g. the strobe speed.
+    for cap in caps {
+        let Some((start, end)) = cap.dmx_range else {
+            continue;
+        };
+        let raw = start.min(u8::MAX as u16) as u8;
+
+        match cap.ty.as_str() {
+            "ShutterStrobe" => {
+                profile.attr = attr::STROBE.into();
+                match cap.shutter_effect.as_deref() {
+                    Some("Strobe") if start != end => profile.range = Some((start, end)),
+                    Some(effect) => {
+                        profile.slots.entry(snake_case(effect)).or_insert(raw);
+                    }
+                    None => {}
+                }
+            }
+            "WheelSlot" => {
+                let wheel_name = cap.wheel.as_deref().unwrap_or(key);
+                let Some(wheel) = fixture.wheels.get(wheel_name) else {
+                    continue;
+                };
+                let is_gobo = wheel.slots.iter().any(|s| s.ty == "Gobo");
+                profile.attr = if is_gobo { attr::GOBO } else { attr::COLOR_WHEEL }.into();
+
+                // Split slots like 1.5 sit between two slots, only take the whole ones.
+                let Some(n) = cap.slot_number.filter(|n| n.fract() == 0.0) else {
+                    continue;
+                };
+                // Slot numbers are 1-indexed.
+                if let Some(slot) = (n as usize).checked_sub(1).and_then(|i| wheel.slots.get(i)) {
+                    let name = match (&slot.name, slot.ty.as_str()) {
+                        (Some(name), _) => name.clone(),
+                        (None, "Open") => "Open".into(),
+                        (None, ty) => format!("{ty} {n}"),
+                    };
+                    profile.slots.entry(snake_case(&name)).or_insert(raw);
+                }
+            }
+            "Prism" => {
+                profile.attr = attr::PRISM.into();
+                profile.slots.entry("on".into()).or_insert(raw);
+            }
+            "NoFunction" if profile.attr == attr::PRISM => {
+                profile.slots.entry("off".into()).or_insert(raw);
+            }
+            _ => {
+                let name = cap.comment.as_deref().map(snake_case).unwrap_or_default();
+                if !name.is_empty() {
+                    profile.slots.entry(name).or_insert(raw);
+                }
+            }
+        }
+    }
+
+    profile
+}
+
+/// Convert an OFL default value, either raw DMX or a percentage, to 0.0..1.0.
+fn default_value(value: Option<&Value>) -> f32 {
+    match value {
+        Some(Value::Number(n)) => n.as_f64().unwrap_or_default() as f32 / 255.0,
+        Some(Value::String(s)) => {
+            let percent = s.strip_suffix('%').and_then(|p| p.parse::<f32>().ok());
+            percent.unwrap_or_default() / 100.0
+        }
+        _ => 0.0,
+    }
+}
+
+/// "Color Wheel" -> "color_wheel"
+fn snake_case(name: &str) -> String {
+    let words = name.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty());
+    words.map(str::to_lowercase).collect::<Vec<_>>().join("_")
+}
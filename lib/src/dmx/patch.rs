@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use anyhow::{Result, bail};
+
+use crate::dmx::{DmxDevice, DmxMerge, DmxScheduler, UNIVERSE_SIZE};
+use crate::prelude::*;
+
+/// The `DmxMerge` source patched fixtures are written into.
+pub const PATCH_SOURCE: &str = "patch";
+
+/// Where a fixture is patched.
+///
+/// Spawn it alongside a `DmxDevice` component to patch the fixture automatically,
+/// changing or removing it moves or unpatches the fixture.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DmxAddress {
+    /// Universe, 1-indexed.
+    pub universe: u16,
+    /// Start address from 1..=512.
+    pub address: usize,
+}
+
+/// Maps fixture entities to their DMX addresses.
+///
+/// Every patched entity with a `DmxDevice` component is encoded into
+/// `DmxMerge` in `PostUpdate`, under the `PATCH_SOURCE` source, and the merged
+/// frames are sent to the `DmxScheduler` if there is one.
+///
+/// Fixtures are usually patched with a `DmxAddress` component rather than
+/// through this directly.
+#[derive(Resource, Default)]
+pub struct Patch {
+    fixtures: HashMap<Entity, Patched>,
+    /// Channels of fixtures which have been unpatched or moved, to release from `DmxMerge`.
+    released: Vec<(u16, Range<usize>)>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Patched {
+    at: DmxAddress,
+    channels: usize,
+}
+
+impl Patched {
+    fn range(&self) -> Range<usize> {
+        self.at.address..self.at.address + self.channels
+    }
+}
+
+impl Patch {
+    /// Patch a fixture which uses `channels` channels, moving it if it's already patched.
+    ///
+    /// Fails if the fixture doesn't fit in the universe or overlaps another fixture.
+    pub fn add(&mut self, entity: Entity, universe: u16, address: usize, channels: usize) -> Result<()> {
+        if universe == 0 {
+            bail!("Can't patch {entity} to universe 0, universes start at 1");
+        }
+        if channels == 0 {
+            bail!("Can't patch {entity} to {universe}/{address}: it has no channels");
+        }
+        match address.checked_add(channels) {
+            Some(end) if address > 0 && end <= UNIVERSE_SIZE => {}
+            _ => bail!(
+                "Can't patch {entity} to {universe}/{address}: {channels} channels don't fit in 1..=512"
+            ),
+        }
+
+        let patched = Patched { at: DmxAddress { universe, address }, channels };
+        let range = patched.range();
+
+        let overlap = self.fixtures.iter().find(|&(&other, p)| {
+            let overlaps = p.range().start < range.end && range.start < p.range().end;
+            other != entity && p.at.universe == universe && overlaps
+        });
+        if let Some((other, p)) = overlap {
+            bail!(
+                "Can't patch {entity} to {universe}/{address}: overlaps with {other} at {universe}/{}",
+                p.at.address
+            );
+        }
+
+        if let Some(prev) = self.fixtures.insert(entity, patched) {
+            self.released.push((prev.at.universe, prev.range()));
+        }
+        Ok(())
+    }
+
+    /// Unpatch a fixture, returning where it was patched.
+    pub fn remove(&mut self, entity: Entity) -> Option<DmxAddress> {
+        let prev = self.fixtures.remove(&entity)?;
+        self.released.push((prev.at.universe, prev.range()));
+        Some(prev.at)
+    }
+
+    /// Where a fixture is patched.
+    pub fn get(&self, entity: Entity) -> Option<DmxAddress> {
+        self.fixtures.get(&entity).map(|p| p.at)
+    }
+
+    /// Every patched fixture and its address.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, DmxAddress)> {
+        self.fixtures.iter().map(|(&entity, p)| (entity, p.at))
+    }
+}
+
+/// System to patch fixtures with a `DmxAddress` component.
+///
+/// Fixtures which couldn't be patched, e.g. because they overlapped another,
+/// are retried whenever any other fixture moves or is unpatched.
+pub fn sync(
+    mut patch: ResMut<Patch>,
+    fixtures: Query<(Entity, Ref<DmxAddress>, One<&dyn DmxDevice>)>,
+    mut removed: RemovedComponents<DmxAddress>,
+) {
+    let mut moved = false;
+    for entity in removed.read() {
+        moved |= patch.remove(entity).is_some();
+    }
+
+    for (entity, at, device) in &fixtures {
+        if at.is_changed() {
+            moved = true;
+            if let Err(e) = patch.add(entity, at.universe, at.address, device.channels()) {
+                error!("{e}");
+            }
+        }
+    }
+
+    if moved {
+        for (entity, at, device) in &fixtures {
+            // Already logged when the address was set.
+            if patch.get(entity) != Some(*at) {
+                let _ = patch.add(entity, at.universe, at.address, device.channels());
+            }
+        }
+    }
+}
+
+/// System to encode every patched `DmxDevice` into `DmxMerge`.
+pub fn encode(mut patch: ResMut<Patch>, mut merge: ResMut<DmxMerge>, devices: Query<One<&dyn DmxDevice>>) {
+    let mut source = merge.source(PATCH_SOURCE);
+
+    // Release first, a moved fixture might be re-written on top.
+    for (universe, channels) in patch.released.drain(..) {
+        source.release(universe, channels);
+    }
+
+    let mut buf = [0u8; UNIVERSE_SIZE];
+    for (&entity, patched) in &patch.fixtures {
+        let Ok(device) = devices.get(entity) else {
+            continue;
+        };
+
+        // Its neighbours could be right after the channels it was patched with.
+        let channels = patched.channels;
+        if device.channels() > channels {
+            warn_once!("{entity} has grown past the {channels} channels it was patched with, skipping");
+            continue;
+        }
+        buf[..channels].fill(0);
+        device.encode(&mut buf[..channels]);
+        source.write(patched.at.universe, patched.at.address, &buf[..channels]);
+    }
+}
+
+/// System to send the merged frames to the `DmxScheduler`.
+pub fn send(merge: Res<DmxMerge>, scheduler: Option<ResMut<DmxScheduler>>) {
+    if let Some(mut scheduler) = scheduler {
+        merge.send(&mut *scheduler);
+    }
+}
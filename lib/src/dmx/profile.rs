@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::color::Rgbw;
+use crate::dmx::DmxDevice;
+use crate::math::Interp;
+use crate::prelude::*;
+
+/// Attribute names shared by profiles, matching the field names on the hand-written devices.
+pub mod attr {
+    pub const ALPHA: &str = "alpha";
+    pub const RED: &str = "red";
+    pub const GREEN: &str = "green";
+    pub const BLUE: &str = "blue";
+    pub const WHITE: &str = "white";
+    pub const PITCH: &str = "pitch";
+    pub const YAW: &str = "yaw";
+    pub const SPEED: &str = "speed";
+    pub const STROBE: &str = "strobe";
+    pub const COLOR_WHEEL: &str = "color_wheel";
+    pub const GOBO: &str = "gobo";
+    pub const PRISM: &str = "prism";
+}
+
+/// A fixture's DMX layout, loaded from a RON file.
+///
+/// ```ron
+/// (
+///     name: "12x3W RGBW par light",
+///     channels: [
+///         (attr: "alpha", offset: 3),
+///         (attr: "red", offset: 4),
+///         (attr: "green", offset: 5),
+///         (attr: "blue", offset: 6),
+///         (attr: "white", offset: 7),
+///     ],
+/// )
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FixtureProfile {
+    pub name: String,
+    /// Total channel footprint. Defaults to one past the highest channel offset.
+    #[serde(default)]
+    pub footprint: Option<usize>,
+    pub channels: Vec<ChannelProfile>,
+}
+
+/// A single attribute of a `FixtureProfile`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChannelProfile {
+    /// Attribute name, see `attr` for the common ones.
+    pub attr: String,
+    /// Offset from the fixture's start address, 0-indexed.
+    pub offset: usize,
+    /// Offset of the fine channel for 16-bit attributes.
+    #[serde(default)]
+    pub fine: Option<usize>,
+    /// Raw DMX range which 0.0..1.0 maps onto. Defaults to the full 8 or 16-bit range.
+    #[serde(default)]
+    pub range: Option<(u16, u16)>,
+    #[serde(default)]
+    pub invert: bool,
+    /// Value from 0.0..1.0 until the attribute is set.
+    #[serde(default)]
+    pub default: f32,
+    /// Named raw values, e.g. color wheel or gobo positions.
+    #[serde(default)]
+    pub slots: BTreeMap<String, u8>,
+}
+
+impl FixtureProfile {
+    /// Parse a profile from a RON string.
+    pub fn parse(ron: &str) -> Result<Self> {
+        let profile: Self = ron::from_str(ron)?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Load a profile from a RON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let ron = std::fs::read_to_string(path).with_context(|| format!("Failed to read {path:?}"))?;
+        Self::parse(&ron).with_context(|| format!("Failed to parse fixture profile {path:?}"))
+    }
+
+    /// Load every `.ron` profile in a directory.
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Vec<Self>> {
+        let dir = dir.as_ref();
+        let mut paths = std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read {dir:?}"))?
+            .map(|entry| Ok(entry?.path()))
+            .collect::<Result<Vec<_>>>()?;
+        paths.retain(|p| p.extension().is_some_and(|ext| ext == "ron"));
+        paths.sort();
+
+        paths.iter().map(Self::load).collect()
+    }
+
+    /// Number of DMX channels the fixture occupies.
+    pub fn footprint(&self) -> usize {
+        self.footprint.unwrap_or_else(|| {
+            let offsets = self.channels.iter().flat_map(|ch| [Some(ch.offset), ch.fine]);
+            offsets.flatten().max().map_or(0, |max| max + 1)
+        })
+    }
+
+    /// Find the channel for an attribute.
+    pub fn channel(&self, attr: &str) -> Option<&ChannelProfile> {
+        self.channels.iter().find(|ch| ch.attr == attr)
+    }
+
+    /// Check that every attribute is named once, and its channels fit in the footprint without overlapping.
+    pub fn validate(&self) -> Result<()> {
+        let footprint = self.footprint();
+        let mut used = vec![None; footprint];
+
+        for (i, ch) in self.channels.iter().enumerate() {
+            let (name, attr) = (&self.name, &ch.attr);
+            if self.channels[..i].iter().any(|other| other.attr == ch.attr) {
+                bail!("{name:?}: {attr:?} is defined more than once");
+            }
+            for offset in [Some(ch.offset), ch.fine].into_iter().flatten() {
+                let Some(slot) = used.get_mut(offset) else {
+                    bail!("{name:?}: channel {offset} of {attr:?} is past the footprint {footprint}");
+                };
+                if let Some(other) = slot.replace(&ch.attr) {
+                    bail!("{name:?}: channel {offset} is used by both {other:?} and {attr:?}");
+                }
+            }
+
+            if let Some((lo, hi)) = ch.range {
+                let max = if ch.fine.is_some() { u16::MAX } else { u8::MAX as u16 };
+                if lo > max || hi > max {
+                    bail!("{name:?}: range of {attr:?} is out of bounds 0..={max}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ChannelProfile {
+    /// The raw value to send for 0.0..1.0.
+    fn raw(&self, value: f32) -> u16 {
+        let max = if self.fine.is_some() { u16::MAX } else { u8::MAX as u16 };
+        let (lo, hi) = self.range.unwrap_or((0, max));
+        let value = value.clamp(0.0, 1.0);
+        let value = if self.invert { 1.0 - value } else { value };
+        value.lerp(lo as f32..hi as f32) as u16
+    }
+
+    /// The value from 0.0..1.0 for a raw value, the inverse of `raw()`.
+    fn value(&self, raw: u16) -> f32 {
+        let max = if self.fine.is_some() { u16::MAX } else { u8::MAX as u16 };
+        let (lo, hi) = self.range.unwrap_or((0, max));
+        if lo == hi {
+            return 0.0;
+        }
+        let value = (raw as f32).ilerp(lo as f32..hi as f32).clamp(0.0, 1.0);
+        if self.invert { 1.0 - value } else { value }
+    }
+}
+
+/// A generic fixture driven by a `FixtureProfile`.
+///
+/// Attributes are set by name from 0.0..1.0, or to one of the profile's named slots.
+#[derive(Component, Clone, Debug)]
+pub struct ProfileFixture {
+    profile: Arc<FixtureProfile>,
+    values: Vec<Value>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Value {
+    Float(f32),
+    Raw(u8),
+}
+
+impl ProfileFixture {
+    /// Fails if the profile isn't valid, which `FixtureProfile::parse()` already checks.
+    pub fn new(profile: Arc<FixtureProfile>) -> Result<Self> {
+        profile.validate()?;
+        let values = profile.channels.iter().map(|ch| Value::Float(ch.default)).collect();
+        Ok(Self { profile, values })
+    }
+
+    pub fn profile(&self) -> &FixtureProfile {
+        &self.profile
+    }
+
+    /// Set an attribute from 0.0..1.0. Does nothing if the profile doesn't have it.
+    pub fn set(&mut self, attr: &str, value: f32) -> &mut Self {
+        if let Some(i) = self.index(attr) {
+            self.values[i] = Value::Float(value);
+        }
+        self
+    }
+
+    /// Get an attribute from 0.0..1.0, or `None` if the profile doesn't have it or it's set to a slot.
+    pub fn get(&self, attr: &str) -> Option<f32> {
+        match self.values[self.index(attr)?] {
+            Value::Float(value) => Some(value),
+            Value::Raw(_) => None,
+        }
+    }
+
+    /// Set an attribute to one of its named slots.
+    pub fn set_slot(&mut self, attr: &str, slot: &str) -> Result<&mut Self> {
+        let Some(i) = self.index(attr) else {
+            bail!("{:?} has no {attr:?} channel", self.profile.name);
+        };
+        let Some(&raw) = self.profile.channels[i].slots.get(slot) else {
+            bail!("{:?} has no {attr:?} slot {slot:?}", self.profile.name);
+        };
+        self.values[i] = Value::Raw(raw);
+        Ok(self)
+    }
+
+    /// Set the RGBW channels, whichever of them the profile has.
+    pub fn set_color(&mut self, color: Rgbw) -> &mut Self {
+        let Rgbw(r, g, b, w) = color;
+        self.set(attr::RED, r)
+            .set(attr::GREEN, g)
+            .set(attr::BLUE, b)
+            .set(attr::WHITE, w)
+    }
+
+    /// `attr::PITCH` from 0.0..1.0, same as `MovingHeadDevice::pitch()`.
+    pub fn pitch(&self) -> f32 {
+        self.get(attr::PITCH).unwrap_or_default()
+    }
+
+    /// `attr::YAW` from 0.0..1.0, same as `MovingHeadDevice::yaw()`.
+    pub fn yaw(&self) -> f32 {
+        self.get(attr::YAW).unwrap_or_default()
+    }
+
+    /// The RGBW channels, with 0.0 for any the profile doesn't have.
+    pub fn color(&self) -> Rgbw {
+        let get = |attr| self.get(attr).unwrap_or_default();
+        Rgbw(get(attr::RED), get(attr::GREEN), get(attr::BLUE), get(attr::WHITE))
+    }
+
+    fn index(&self, attr: &str) -> Option<usize> {
+        self.profile.channels.iter().position(|ch| ch.attr == attr)
+    }
+}
+
+impl DmxDevice for ProfileFixture {
+    fn channels(&self) -> usize {
+        self.profile.footprint()
+    }
+
+    fn encode(&self, buf: &mut [u8]) {
+        for (ch, &value) in self.profile.channels.iter().zip(&self.values) {
+            match value {
+                Value::Raw(raw) => {
+                    buf[ch.offset] = raw;
+                    if let Some(fine) = ch.fine {
+                        buf[fine] = 0;
+                    }
+                }
+                Value::Float(value) => {
+                    let raw = ch.raw(value);
+                    match ch.fine {
+                        Some(fine) => [buf[ch.offset], buf[fine]] = raw.to_be_bytes(),
+                        None => buf[ch.offset] = raw as u8,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Channels with named slots decode to the raw value, since values between slots aren't meaningful.
+    fn decode(&mut self, buf: &[u8]) {
+        for (ch, value) in self.profile.channels.iter().zip(&mut self.values) {
+            if !ch.slots.is_empty() {
+                *value = Value::Raw(buf[ch.offset]);
+                continue;
+            }
+
+            let raw = match ch.fine {
+                Some(fine) => u16::from_be_bytes([buf[ch.offset], buf[fine]]),
+                None => buf[ch.offset] as u16,
+            };
+            *value = Value::Float(ch.value(raw));
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{Result, anyhow, bail};
+
+use super::{
+    CommandClass, DeviceInfo, RdmMessage, RdmTransport, ResponseType, Uid, decode_disc_response, pid,
+};
+
+/// RDM controller, which discovers and configures fixtures over an `RdmTransport`.
+pub struct RdmController<T> {
+    transport: T,
+    uid: Uid,
+    transaction: u8,
+}
+
+impl<T: RdmTransport> RdmController<T> {
+    /// Constructs a new controller which sends from `uid`.
+    pub fn new(transport: T, uid: Uid) -> Self {
+        Self { transport, uid, transaction: 0 }
+    }
+
+    /// Find every responder, using the binary search from E1.20 section 7.
+    pub fn discover(&mut self) -> Result<Vec<Uid>> {
+        self.request(Uid::BROADCAST, CommandClass::Discovery, pid::DISC_UN_MUTE, vec![])?;
+
+        let mut found = vec![];
+        self.branch(Uid::MIN, Uid::MAX, &mut found)?;
+        found.sort();
+        Ok(found)
+    }
+
+    /// Search a range of UIDs, muting every device found so it stops responding.
+    fn branch(&mut self, lo: Uid, hi: Uid, found: &mut Vec<Uid>) -> Result<()> {
+        let mut data = lo.to_bytes().to_vec();
+        data.extend_from_slice(&hi.to_bytes());
+        let replies = self.request(Uid::BROADCAST, CommandClass::Discovery, pid::DISC_UNIQUE_BRANCH, data)?;
+        if replies.is_empty() {
+            return Ok(());
+        }
+
+        // Transports which don't collide can hand us several valid responses at once.
+        let uids = replies.iter().map(|r| decode_disc_response(r)).collect::<Option<Vec<_>>>();
+        match uids {
+            Some(uids) => {
+                let mut muted = false;
+                for uid in uids {
+                    if !found.contains(&uid) && self.mute(uid)? {
+                        found.push(uid);
+                        muted = true;
+                    }
+                }
+                // Others might have been hidden behind the ones we found.
+                if muted {
+                    self.branch(lo, hi, found)?;
+                }
+            }
+            // Collision, split the range and try again.
+            None if lo != hi => {
+                let mid = lo.midpoint(hi);
+                self.branch(lo, mid, found)?;
+                self.branch(mid.next(), hi, found)?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Mute a device, returning whether it acknowledged.
+    fn mute(&mut self, uid: Uid) -> Result<bool> {
+        let replies = self.request(uid, CommandClass::Discovery, pid::DISC_MUTE, vec![])?;
+        Ok(replies.iter().filter_map(|r| RdmMessage::decode(r)).any(|r| r.src == uid))
+    }
+
+    pub fn device_info(&mut self, uid: Uid) -> Result<DeviceInfo> {
+        let data = self.command(uid, CommandClass::Get, pid::DEVICE_INFO, vec![])?;
+        DeviceInfo::decode(&data).ok_or_else(|| anyhow!("Malformed DEVICE_INFO from {uid}"))
+    }
+
+    pub fn start_address(&mut self, uid: Uid) -> Result<u16> {
+        let data = self.command(uid, CommandClass::Get, pid::DMX_START_ADDRESS, vec![])?;
+        let bytes = data.try_into().map_err(|_| anyhow!("Malformed DMX_START_ADDRESS from {uid}"))?;
+        Ok(u16::from_be_bytes(bytes))
+    }
+
+    /// Readdress a device, from 1..=512.
+    pub fn set_start_address(&mut self, uid: Uid, address: u16) -> Result<()> {
+        let data = address.to_be_bytes().to_vec();
+        self.command(uid, CommandClass::Set, pid::DMX_START_ADDRESS, data)?;
+        Ok(())
+    }
+
+    /// Make a device flash or otherwise make itself known.
+    pub fn identify(&mut self, uid: Uid, on: bool) -> Result<()> {
+        self.command(uid, CommandClass::Set, pid::IDENTIFY_DEVICE, vec![on as u8])?;
+        Ok(())
+    }
+
+    /// Send a GET or SET to a single device, returning the parameter data of its ACK.
+    fn command(&mut self, uid: Uid, cc: CommandClass, pid: u16, data: Vec<u8>) -> Result<Vec<u8>> {
+        let transaction = self.transaction;
+        let replies = self.request(uid, cc, pid, data)?;
+
+        let response = replies
+            .iter()
+            .filter_map(|r| RdmMessage::decode(r))
+            .find(|r| r.src == uid && r.transaction == transaction && r.command_class == cc.response())
+            .ok_or_else(|| anyhow!("No response from {uid} for PID {pid:#06X}"))?;
+
+        match response.port_or_response {
+            r if r == ResponseType::Ack as u8 => Ok(response.data),
+            r if r == ResponseType::NackReason as u8 => {
+                let reason = response.data.get(..2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+                bail!("{uid} NACKed PID {pid:#06X} with reason {reason:#06X?}")
+            }
+            r => bail!("Unsupported response type {r:#04X} from {uid} for PID {pid:#06X}"),
+        }
+    }
+
+    fn request(&mut self, dest: Uid, cc: CommandClass, pid: u16, data: Vec<u8>) -> Result<Vec<Vec<u8>>> {
+        let request = RdmMessage {
+            dest,
+            src: self.uid,
+            transaction: self.transaction,
+            port_or_response: 1,
+            message_count: 0,
+            sub_device: 0,
+            command_class: cc,
+            pid,
+            data,
+        };
+        self.transaction = self.transaction.wrapping_add(1);
+        self.transport.send(&request.encode())
+    }
+}
@@ -0,0 +1,329 @@
+//! RDM (Remote Device Management, ANSI E1.20).
+//!
+//! RDM lets a controller discover the fixtures on a line, ask them what they
+//! are, and change their start address without climbing a truss.
+//!
+//! Messages are transport agnostic: `RdmController` talks to fixtures through
+//! any `RdmTransport`, and `RdmResponder` answers them as a simulated fixture.
+//! `RdmUdp` and `RdmUdpResponder` carry raw E1.20 packets over UDP, as a local
+//! stand-in for RDMnet.
+
+use std::fmt;
+
+mod controller;
+pub use controller::RdmController;
+
+mod responder;
+pub use responder::RdmResponder;
+
+mod udp;
+pub use udp::{PORT, RdmUdp, RdmUdpResponder};
+
+/// RDM start code, sent in place of the DMX null start code.
+pub const START_CODE: u8 = 0xCC;
+const SUB_START_CODE: u8 = 0x01;
+
+/// Parameter IDs we support.
+pub mod pid {
+    pub const DISC_UNIQUE_BRANCH: u16 = 0x0001;
+    pub const DISC_MUTE: u16 = 0x0002;
+    pub const DISC_UN_MUTE: u16 = 0x0003;
+    pub const DEVICE_INFO: u16 = 0x0060;
+    pub const DMX_START_ADDRESS: u16 = 0x00F0;
+    pub const IDENTIFY_DEVICE: u16 = 0x1000;
+}
+
+/// NACK reason codes.
+pub mod nack {
+    pub const UNKNOWN_PID: u16 = 0x0000;
+    pub const FORMAT_ERROR: u16 = 0x0001;
+    pub const UNSUPPORTED_COMMAND_CLASS: u16 = 0x0005;
+    pub const DATA_OUT_OF_RANGE: u16 = 0x0006;
+}
+
+/// A 48-bit RDM unique ID: a 16-bit ESTA manufacturer ID and a 32-bit device ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uid(u64);
+
+impl Uid {
+    pub const MIN: Self = Self(0);
+    pub const MAX: Self = Self(0xFFFF_FFFF_FFFE);
+    /// Addresses every device.
+    pub const BROADCAST: Self = Self(0xFFFF_FFFF_FFFF);
+
+    pub const fn new(manufacturer: u16, device: u32) -> Self {
+        Self(((manufacturer as u64) << 32) | device as u64)
+    }
+
+    pub fn manufacturer(self) -> u16 {
+        (self.0 >> 32) as u16
+    }
+
+    pub fn device(self) -> u32 {
+        self.0 as u32
+    }
+
+    /// Whether a message sent to `dest` is addressed to this device, directly or by broadcast.
+    pub fn matches(self, dest: Uid) -> bool {
+        let manufacturer_broadcast = dest.device() == u32::MAX && dest.manufacturer() == self.manufacturer();
+        dest == self || dest == Self::BROADCAST || manufacturer_broadcast
+    }
+
+    pub fn to_bytes(self) -> [u8; 6] {
+        let [_, _, bytes @ ..] = self.0.to_be_bytes();
+        bytes
+    }
+
+    pub fn from_bytes(bytes: [u8; 6]) -> Self {
+        let [a, b, c, d, e, f] = bytes;
+        Self(u64::from_be_bytes([0, 0, a, b, c, d, e, f]))
+    }
+
+    fn midpoint(self, other: Self) -> Self {
+        Self(self.0 + (other.0 - self.0) / 2)
+    }
+
+    fn next(self) -> Self {
+        Self(self.0 + 1)
+    }
+}
+
+impl fmt::Display for Uid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04X}:{:08X}", self.manufacturer(), self.device())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CommandClass {
+    Discovery = 0x10,
+    DiscoveryResponse = 0x11,
+    Get = 0x20,
+    GetResponse = 0x21,
+    Set = 0x30,
+    SetResponse = 0x31,
+}
+
+impl CommandClass {
+    fn from_byte(byte: u8) -> Option<Self> {
+        Some(match byte {
+            0x10 => Self::Discovery,
+            0x11 => Self::DiscoveryResponse,
+            0x20 => Self::Get,
+            0x21 => Self::GetResponse,
+            0x30 => Self::Set,
+            0x31 => Self::SetResponse,
+            _ => return None,
+        })
+    }
+
+    /// The command class of the response to this request.
+    pub fn response(self) -> Self {
+        match self {
+            Self::Discovery | Self::DiscoveryResponse => Self::DiscoveryResponse,
+            Self::Get | Self::GetResponse => Self::GetResponse,
+            Self::Set | Self::SetResponse => Self::SetResponse,
+        }
+    }
+}
+
+/// Response types, sent in place of the port ID in responses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ResponseType {
+    Ack = 0x00,
+    AckTimer = 0x01,
+    NackReason = 0x02,
+    AckOverflow = 0x03,
+}
+
+/// An RDM request or response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RdmMessage {
+    pub dest: Uid,
+    pub src: Uid,
+    pub transaction: u8,
+    /// Port ID in requests, `ResponseType` in responses.
+    pub port_or_response: u8,
+    pub message_count: u8,
+    pub sub_device: u16,
+    pub command_class: CommandClass,
+    pub pid: u16,
+    pub data: Vec<u8>,
+}
+
+impl RdmMessage {
+    /// Encode the message, including the start code and checksum.
+    pub fn encode(&self) -> Vec<u8> {
+        assert!(self.data.len() <= 231, "RDM parameter data is limited to 231 bytes");
+
+        let len = 24 + self.data.len();
+        let mut packet = Vec::with_capacity(len + 2);
+        packet.push(START_CODE);
+        packet.push(SUB_START_CODE);
+        packet.push(len as u8);
+        packet.extend_from_slice(&self.dest.to_bytes());
+        packet.extend_from_slice(&self.src.to_bytes());
+        packet.push(self.transaction);
+        packet.push(self.port_or_response);
+        packet.push(self.message_count);
+        packet.extend_from_slice(&self.sub_device.to_be_bytes());
+        packet.push(self.command_class as u8);
+        packet.extend_from_slice(&self.pid.to_be_bytes());
+        packet.push(self.data.len() as u8);
+        packet.extend_from_slice(&self.data);
+
+        let checksum = checksum(&packet);
+        packet.extend_from_slice(&checksum.to_be_bytes());
+        packet
+    }
+
+    /// Decode a message, checking its length and checksum.
+    pub fn decode(packet: &[u8]) -> Option<Self> {
+        if packet.len() < 26 || packet[0] != START_CODE || packet[1] != SUB_START_CODE {
+            return None;
+        }
+
+        let len = packet[2] as usize;
+        let pdl = packet[23] as usize;
+        if len != 24 + pdl || packet.len() < len + 2 {
+            return None;
+        }
+        if checksum(&packet[..len]) != u16::from_be_bytes([packet[len], packet[len + 1]]) {
+            return None;
+        }
+
+        let uid_at = |i: usize| Uid::from_bytes(packet[i..i + 6].try_into().unwrap());
+        Some(Self {
+            dest: uid_at(3),
+            src: uid_at(9),
+            transaction: packet[15],
+            port_or_response: packet[16],
+            message_count: packet[17],
+            sub_device: u16::from_be_bytes([packet[18], packet[19]]),
+            command_class: CommandClass::from_byte(packet[20])?,
+            pid: u16::from_be_bytes([packet[21], packet[22]]),
+            data: packet[24..len].to_vec(),
+        })
+    }
+
+    /// Build the response to this request.
+    pub fn respond(&self, src: Uid, response: ResponseType, data: Vec<u8>) -> Self {
+        Self {
+            dest: self.src,
+            src,
+            transaction: self.transaction,
+            port_or_response: response as u8,
+            message_count: 0,
+            sub_device: self.sub_device,
+            command_class: self.command_class.response(),
+            pid: self.pid,
+            data,
+        }
+    }
+}
+
+/// Payload of a DEVICE_INFO response.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub protocol_version: u16,
+    pub model_id: u16,
+    pub product_category: u16,
+    pub software_version: u32,
+    pub footprint: u16,
+    pub personality: u8,
+    pub personality_count: u8,
+    /// 1..=512, or 0xFFFF if the device doesn't use any DMX channels.
+    pub start_address: u16,
+    pub sub_device_count: u16,
+    pub sensor_count: u8,
+}
+
+impl DeviceInfo {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(19);
+        data.extend_from_slice(&self.protocol_version.to_be_bytes());
+        data.extend_from_slice(&self.model_id.to_be_bytes());
+        data.extend_from_slice(&self.product_category.to_be_bytes());
+        data.extend_from_slice(&self.software_version.to_be_bytes());
+        data.extend_from_slice(&self.footprint.to_be_bytes());
+        data.push(self.personality);
+        data.push(self.personality_count);
+        data.extend_from_slice(&self.start_address.to_be_bytes());
+        data.extend_from_slice(&self.sub_device_count.to_be_bytes());
+        data.push(self.sensor_count);
+        data
+    }
+
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        if data.len() != 19 {
+            return None;
+        }
+
+        let u16_at = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+        Some(Self {
+            protocol_version: u16_at(0),
+            model_id: u16_at(2),
+            product_category: u16_at(4),
+            software_version: u32::from_be_bytes([data[6], data[7], data[8], data[9]]),
+            footprint: u16_at(10),
+            personality: data[12],
+            personality_count: data[13],
+            start_address: u16_at(14),
+            sub_device_count: u16_at(16),
+            sensor_count: data[18],
+        })
+    }
+}
+
+/// A way to get RDM packets to and from fixtures.
+pub trait RdmTransport {
+    /// Send a request and return the responses received before timing out.
+    ///
+    /// Broadcasts get no responses, and DISC_UNIQUE_BRANCH can get any number, which
+    /// on a real DMX line collide into a single unreadable response.
+    fn send(&mut self, request: &[u8]) -> anyhow::Result<Vec<Vec<u8>>>;
+}
+
+/// Encode the response to DISC_UNIQUE_BRANCH.
+///
+/// It has no header so that responses from several devices collide predictably:
+/// a preamble, then the UID and checksum with each byte sent twice, OR'd with
+/// 0xAA and 0x55.
+pub fn encode_disc_response(uid: Uid) -> Vec<u8> {
+    let euid = uid
+        .to_bytes()
+        .into_iter()
+        .flat_map(|b| [b | 0xAA, b | 0x55])
+        .collect::<Vec<_>>();
+    let [hi, lo] = checksum(&euid).to_be_bytes();
+
+    let mut packet = vec![0xFE; 7];
+    packet.push(0xAA);
+    packet.extend_from_slice(&euid);
+    packet.extend_from_slice(&[hi | 0xAA, hi | 0x55, lo | 0xAA, lo | 0x55]);
+    packet
+}
+
+/// Decode a DISC_UNIQUE_BRANCH response, or `None` if it's garbled by a collision.
+pub fn decode_disc_response(packet: &[u8]) -> Option<Uid> {
+    // Skip the preamble, which can be 0 to 7 bytes.
+    let start = packet.iter().take(8).position(|&b| b == 0xAA)?;
+    let body = packet.get(start + 1..start + 17)?;
+    let decode = |pair: &[u8]| pair[0] & pair[1];
+
+    let (euid, sum) = body.split_at(12);
+    let uid = euid.chunks(2).map(decode).collect::<Vec<_>>();
+    let sum = sum.chunks(2).map(decode).collect::<Vec<_>>();
+    if checksum(euid) != u16::from_be_bytes([sum[0], sum[1]]) {
+        return None;
+    }
+
+    Some(Uid::from_bytes(uid.try_into().ok()?))
+}
+
+/// 16-bit additive checksum.
+fn checksum(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |sum, &b| sum.wrapping_add(b as u16))
+}
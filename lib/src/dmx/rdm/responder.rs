@@ -0,0 +1,123 @@
+use super::{CommandClass, DeviceInfo, RdmMessage, ResponseType, Uid, encode_disc_response, nack, pid};
+
+/// A simulated RDM fixture, which answers requests from an `RdmController`.
+#[derive(Clone, Debug)]
+pub struct RdmResponder {
+    pub uid: Uid,
+    pub model_id: u16,
+    pub product_category: u16,
+    pub software_version: u32,
+    pub footprint: u16,
+    /// 1..=512
+    pub start_address: u16,
+    /// Whether the fixture has been asked to identify itself.
+    pub identify: bool,
+    /// Muted devices don't respond to DISC_UNIQUE_BRANCH.
+    pub muted: bool,
+}
+
+impl RdmResponder {
+    pub fn new(uid: Uid, footprint: u16, start_address: u16) -> Self {
+        Self {
+            uid,
+            model_id: 0,
+            product_category: 0,
+            software_version: 0,
+            footprint,
+            start_address,
+            identify: false,
+            muted: false,
+        }
+    }
+
+    pub fn device_info(&self) -> DeviceInfo {
+        DeviceInfo {
+            protocol_version: 0x0100,
+            model_id: self.model_id,
+            product_category: self.product_category,
+            software_version: self.software_version,
+            footprint: self.footprint,
+            personality: 1,
+            personality_count: 1,
+            start_address: self.start_address,
+            sub_device_count: 0,
+            sensor_count: 0,
+        }
+    }
+
+    /// Handle a request, returning the response to send back if there is one.
+    pub fn handle(&mut self, packet: &[u8]) -> Option<Vec<u8>> {
+        let request = RdmMessage::decode(packet)?;
+        if !self.uid.matches(request.dest) {
+            return None;
+        }
+
+        // Discovery has its own response format, and is the only thing answered when broadcast.
+        if request.command_class == CommandClass::Discovery && request.pid == pid::DISC_UNIQUE_BRANCH {
+            let lo = Uid::from_bytes(request.data.get(..6)?.try_into().ok()?);
+            let hi = Uid::from_bytes(request.data.get(6..12)?.try_into().ok()?);
+            let in_range = lo <= self.uid && self.uid <= hi;
+            return (in_range && !self.muted).then(|| encode_disc_response(self.uid));
+        }
+
+        // Broadcasts still take effect, but nobody answers them.
+        let (response, data) = self.respond(&request);
+        if request.dest != self.uid {
+            return None;
+        }
+        Some(request.respond(self.uid, response, data).encode())
+    }
+
+    fn respond(&mut self, request: &RdmMessage) -> (ResponseType, Vec<u8>) {
+        let nack = |reason: u16| (ResponseType::NackReason, reason.to_be_bytes().to_vec());
+        let ack = |data: Vec<u8>| (ResponseType::Ack, data);
+
+        match (request.command_class, request.pid, request.data.as_slice()) {
+            (CommandClass::Discovery, pid::DISC_MUTE, []) => {
+                self.muted = true;
+                // Control field, no flags set.
+                ack(vec![0, 0])
+            }
+            (CommandClass::Discovery, pid::DISC_UN_MUTE, []) => {
+                self.muted = false;
+                ack(vec![0, 0])
+            }
+            (CommandClass::Get, pid::DEVICE_INFO, []) => ack(self.device_info().encode()),
+            (CommandClass::Get, pid::DMX_START_ADDRESS, []) => ack(self.start_address.to_be_bytes().to_vec()),
+            (CommandClass::Set, pid::DMX_START_ADDRESS, &[hi, lo]) => {
+                let address = u16::from_be_bytes([hi, lo]);
+                let end = address as usize + self.footprint.max(1) as usize - 1;
+                if address == 0 || end > 512 {
+                    return nack(nack::DATA_OUT_OF_RANGE);
+                }
+                self.start_address = address;
+                ack(vec![])
+            }
+            (CommandClass::Get, pid::IDENTIFY_DEVICE, []) => ack(vec![self.identify as u8]),
+            (CommandClass::Set, pid::IDENTIFY_DEVICE, &[on @ (0 | 1)]) => {
+                self.identify = on == 1;
+                ack(vec![])
+            }
+            (CommandClass::Set, pid::IDENTIFY_DEVICE, &[_]) => nack(nack::DATA_OUT_OF_RANGE),
+            (cc, id, _) if supports(cc, id) => nack(nack::FORMAT_ERROR),
+            (_, id, _) if known(id) => nack(nack::UNSUPPORTED_COMMAND_CLASS),
+            _ => nack(nack::UNKNOWN_PID),
+        }
+    }
+}
+
+/// Whether we handle PID `id` with command class `cc`.
+fn supports(cc: CommandClass, id: u16) -> bool {
+    match cc {
+        CommandClass::Discovery => matches!(id, pid::DISC_MUTE | pid::DISC_UN_MUTE),
+        CommandClass::Get => matches!(id, pid::DEVICE_INFO | pid::DMX_START_ADDRESS | pid::IDENTIFY_DEVICE),
+        CommandClass::Set => matches!(id, pid::DMX_START_ADDRESS | pid::IDENTIFY_DEVICE),
+        _ => false,
+    }
+}
+
+fn known(id: u16) -> bool {
+    [CommandClass::Discovery, CommandClass::Get, CommandClass::Set]
+        .into_iter()
+        .any(|cc| supports(cc, id))
+}
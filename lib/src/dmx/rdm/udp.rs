@@ -0,0 +1,118 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+use super::{RdmResponder, RdmTransport};
+use crate::prelude::*;
+
+/// UDP port RDM packets are exchanged on.
+///
+/// RDMnet proper runs over TCP through a broker, this is just enough to talk
+/// to simulated fixtures on the local network.
+pub const PORT: u16 = 5569;
+
+/// How long to wait for responses to a request.
+const TIMEOUT: Duration = Duration::from_millis(50);
+
+/// RDM transport which sends raw E1.20 packets over UDP.
+pub struct RdmUdp {
+    sock: UdpSocket,
+    dest: SocketAddr,
+}
+
+impl RdmUdp {
+    /// Constructs a new transport. `dest_ip` can be a responder or a broadcast address.
+    pub fn new(dest_ip: &str) -> Result<Self> {
+        let dest: IpAddr = dest_ip.parse().with_context(|| format!("failed to parse ip: {dest_ip:?}"))?;
+        let dest = SocketAddr::new(dest, PORT);
+
+        let sock = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).context("Failed to bind RDM socket")?;
+        sock.set_broadcast(true)?;
+
+        Ok(Self { sock, dest })
+    }
+}
+
+impl RdmTransport for RdmUdp {
+    fn send(&mut self, request: &[u8]) -> Result<Vec<Vec<u8>>> {
+        // Drop any stragglers from a previous request.
+        self.sock.set_nonblocking(true)?;
+        let mut buf = [0u8; 512];
+        while self.sock.recv_from(&mut buf).is_ok() {}
+        self.sock.set_nonblocking(false)?;
+
+        self.sock.send_to(request, self.dest)?;
+
+        let mut responses = vec![];
+        let deadline = Instant::now() + TIMEOUT;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            self.sock.set_read_timeout(Some(remaining))?;
+
+            match self.sock.recv_from(&mut buf) {
+                Ok((size, _)) => responses.push(buf[..size].to_vec()),
+                Err(e)
+                    if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) =>
+                {
+                    break;
+                }
+                Err(e) => return Err(e).context("Failed to receive RDM response"),
+            }
+        }
+        Ok(responses)
+    }
+}
+
+/// Hosts simulated `RdmResponder`s, answering requests from an `RdmUdp` transport.
+#[derive(Resource, Clone)]
+pub struct RdmUdpResponder {
+    devices: Arc<Mutex<Vec<RdmResponder>>>,
+}
+
+impl RdmUdpResponder {
+    /// Constructs a new responder, listening on `PORT` in a worker thread.
+    pub fn new(devices: Vec<RdmResponder>) -> Result<Self> {
+        let sock = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, PORT))
+            .with_context(|| format!("Failed to bind RDM port {PORT}"))?;
+
+        let devices = Arc::new(Mutex::new(devices));
+        let responder = Self { devices: Arc::clone(&devices) };
+
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 512];
+            loop {
+                let (size, source) = match sock.recv_from(&mut buf) {
+                    Ok(recv) => recv,
+                    Err(e) => {
+                        error!("Failed to receive on RDM socket: {e}");
+                        continue;
+                    }
+                };
+
+                let responses = devices
+                    .lock()
+                    .unwrap()
+                    .iter_mut()
+                    .filter_map(|device| device.handle(&buf[..size]))
+                    .collect::<Vec<_>>();
+                for response in responses {
+                    if let Err(e) = sock.send_to(&response, source) {
+                        error!("Failed to send RDM response to {source}: {e}");
+                    }
+                }
+            }
+        });
+
+        Ok(responder)
+    }
+
+    /// The simulated devices, which are updated as requests come in.
+    pub fn devices(&self) -> MutexGuard<'_, Vec<RdmResponder>> {
+        self.devices.lock().unwrap()
+    }
+}
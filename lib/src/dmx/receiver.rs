@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::{Mutex, mpsc};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+use crate::prelude::*;
+use crate::{artnet, e131};
+
+/// How long a source can go silent before another of the same or lower priority can take over.
+const SOURCE_TIMEOUT: Duration = Duration::from_millis(2500);
+
+/// The protocol a frame was received over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DmxProtocol {
+    E131,
+    ArtNet,
+}
+
+/// A DMX frame received from the network.
+#[derive(Clone, Debug)]
+pub struct DmxInputFrame {
+    /// Universe, 1-indexed.
+    pub universe: u16,
+    /// Start code followed by up to 512 channels, same layout as `DmxOutput::send_universe()`.
+    pub data: Vec<u8>,
+
+    pub protocol: DmxProtocol,
+    pub source: SocketAddr,
+    /// E1.31 priority from 0..=200. Art-Net has no priority, so it's always 100.
+    pub priority: u8,
+    pub received: Instant,
+}
+
+/// E1.31 and Art-Net receiver.
+///
+/// Listens on a set of universes and keeps the latest frame for each, e.g. to
+/// monitor or take over from an existing console. When several sources send the
+/// same universe, the highest E1.31 priority wins, and between equals the current
+/// source is kept until it goes silent, rather than flickering between them.
+#[derive(Resource)]
+pub struct DmxReceiver {
+    universes: Vec<u16>,
+    rx: Mutex<mpsc::Receiver<Received>>,
+    frames: HashMap<u16, DmxInputFrame>,
+}
+
+/// A frame, or a source terminating its stream.
+enum Received {
+    Frame(DmxInputFrame),
+    Terminated(u16, SocketAddr),
+}
+
+impl DmxReceiver {
+    /// Constructs a new receiver listening on the given universes, 1-indexed.
+    pub fn new(universes: &[u16]) -> Result<Self> {
+        Self::new_inner(universes)
+            .with_context(|| format!("Failed to initialize DMX receiver on {universes:?}"))
+    }
+
+    fn new_inner(universes: &[u16]) -> Result<Self> {
+        let (tx, rx) = mpsc::channel();
+
+        let e131_sock = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, e131::DEFAULT_PORT))
+            .context("Failed to bind E1.31 socket")?;
+        for &universe in universes {
+            e131_sock
+                .join_multicast_v4(&e131::multicast_addr(universe), &Ipv4Addr::UNSPECIFIED)
+                .with_context(|| format!("Failed to join E1.31 multicast for universe {universe}"))?;
+        }
+        spawn("E1.31", e131_sock, tx.clone(), |packet, source| {
+            let packet = e131::parse_data(packet)?;
+            if packet.terminated {
+                return Some(Received::Terminated(packet.universe, source));
+            }
+
+            Some(Received::Frame(DmxInputFrame {
+                universe: packet.universe,
+                data: packet.data.to_vec(),
+                protocol: DmxProtocol::E131,
+                source,
+                priority: packet.priority,
+                received: Instant::now(),
+            }))
+        });
+
+        // An `ArtNet` sender in the same process might already hold the port, which is fine.
+        match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, artnet::PORT)) {
+            Ok(artnet_sock) => spawn("Art-Net", artnet_sock, tx, |packet, source| {
+                let (universe, data) = artnet::parse_dmx(packet)?;
+                let mut frame = Vec::with_capacity(1 + data.len());
+                frame.push(0); // start code
+                frame.extend_from_slice(data);
+
+                Some(Received::Frame(DmxInputFrame {
+                    universe,
+                    data: frame,
+                    protocol: DmxProtocol::ArtNet,
+                    source,
+                    priority: 100,
+                    received: Instant::now(),
+                }))
+            }),
+            Err(e) => warn!("Failed to bind Art-Net port {}, only receiving E1.31: {e}", artnet::PORT),
+        }
+
+        Ok(Self { universes: universes.to_vec(), rx: Mutex::new(rx), frames: HashMap::new() })
+    }
+
+    /// The latest frame received on a universe, 1-indexed.
+    pub fn frame(&self, universe: u16) -> Option<&DmxInputFrame> {
+        self.frames.get(&universe)
+    }
+
+    /// The latest frame received on each universe.
+    pub fn frames(&self) -> impl Iterator<Item = &DmxInputFrame> {
+        self.frames.values()
+    }
+
+    /// Pull in any pending frames.
+    fn recv(&mut self) {
+        while let Ok(received) = self.rx.lock().unwrap().try_recv() {
+            match received {
+                Received::Frame(frame) => {
+                    if !self.universes.contains(&frame.universe) {
+                        continue;
+                    }
+
+                    let take = match self.frames.get(&frame.universe) {
+                        Some(prev) => {
+                            prev.source == frame.source
+                                || frame.priority > prev.priority
+                                || prev.received.elapsed() > SOURCE_TIMEOUT
+                        }
+                        None => true,
+                    };
+                    if take {
+                        self.frames.insert(frame.universe, frame);
+                    }
+                }
+                Received::Terminated(universe, source) => {
+                    if self.frames.get(&universe).is_some_and(|prev| prev.source == source) {
+                        self.frames.remove(&universe);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Spawn a worker thread which parses incoming packets and pushes them to the back of the queue.
+fn spawn(
+    protocol: &'static str,
+    sock: UdpSocket,
+    tx: mpsc::Sender<Received>,
+    parse: impl Fn(&[u8], SocketAddr) -> Option<Received> + Send + 'static,
+) {
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 1024];
+        loop {
+            let (size, source) = match sock.recv_from(&mut buf) {
+                Ok(recv) => recv,
+                // Windows reports ICMP port unreachable from an earlier send here.
+                Err(e) if matches!(e.kind(), ErrorKind::Interrupted | ErrorKind::ConnectionReset) => {
+                    continue;
+                }
+                // Anything else won't go away by retrying, e.g. the socket was closed.
+                Err(e) => {
+                    error!("Failed to receive on {protocol} socket, giving up: {e}");
+                    return;
+                }
+            };
+
+            if let Some(received) = parse(&buf[..size], source) {
+                // The main thread exited and we're shutting down anyways.
+                if tx.send(received).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// System to pull in received frames.
+pub fn update(mut receiver: ResMut<DmxReceiver>) {
+    receiver.recv();
+}
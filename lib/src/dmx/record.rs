@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+
+use crate::dmx::{DmxOutput, UNIVERSE_SIZE};
+use crate::prelude::*;
+
+/// File header, bump the version on format changes.
+const MAGIC: &[u8; 8] = b"RAVYDMX1";
+
+/// Records every DMX frame sent to a file, to replay later with `DmxPlayer`.
+///
+/// It's an output itself, so it's chained alongside the real one, e.g.
+/// `DmxScheduler::new((E131::new(ip)?, DmxRecorder::create("show.dmx")?))`.
+///
+/// # Format
+///
+/// `RAVYDMX1`, followed by one record per frame: milliseconds since recording
+/// started (u32), universe (u16), length (u16), then the start code and
+/// channels. Integers are little endian. Frames identical to the last one
+/// recorded on the same universe are skipped, so keep-alive resends and idle
+/// stretches cost nothing.
+#[derive(Resource)]
+pub struct DmxRecorder {
+    path: PathBuf,
+    file: Option<BufWriter<File>>,
+    start: Instant,
+    /// Last frame recorded per universe.
+    last: HashMap<u16, Vec<u8>>,
+}
+
+/// A recorded frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DmxRecord {
+    /// Time since recording started.
+    pub time: Duration,
+    /// Universe, 1-indexed.
+    pub universe: u16,
+    /// Start code followed by up to 512 channels.
+    pub data: Vec<u8>,
+}
+
+impl DmxRecorder {
+    /// Start recording to a new file, overwriting it if it exists.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path).with_context(|| format!("Failed to create DMX recording {path:?}"))?;
+
+        let mut file = BufWriter::new(file);
+        file.write_all(MAGIC)?;
+        Ok(Self {
+            path: path.to_owned(),
+            file: Some(file),
+            start: Instant::now(),
+            last: HashMap::new(),
+        })
+    }
+
+    /// Time since recording started.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn write(&mut self, universe: u16, payload: &[u8]) -> io::Result<()> {
+        let Some(file) = &mut self.file else {
+            return Ok(());
+        };
+
+        let time = self.start.elapsed().as_millis().min(u32::MAX as u128) as u32;
+        file.write_all(&time.to_le_bytes())?;
+        file.write_all(&universe.to_le_bytes())?;
+        file.write_all(&(payload.len() as u16).to_le_bytes())?;
+        file.write_all(payload)
+    }
+
+    /// Stop recording after an error, so a full disk doesn't spam the log every frame.
+    fn fail(&mut self, e: io::Error) {
+        error!("Failed to write DMX recording {:?}, recording stopped: {e}", self.path);
+        self.file = None;
+    }
+}
+
+impl DmxOutput for DmxRecorder {
+    fn send_universe(&mut self, universe: u16, payload: &[u8]) {
+        assert!(payload.len() <= UNIVERSE_SIZE);
+
+        if self.last.get(&universe).is_some_and(|last| last == payload) {
+            return;
+        }
+        self.last.insert(universe, payload.to_vec());
+
+        if let Err(e) = self.write(universe, payload) {
+            self.fail(e);
+        }
+    }
+
+    /// Flushes to disk, so at most one frame is lost if we crash.
+    fn flush(&mut self) {
+        if let Some(Err(e)) = self.file.as_mut().map(|f| f.flush()) {
+            self.fail(e);
+        }
+    }
+}
+
+/// Plays back a recording from `DmxRecorder`.
+///
+/// Records are read as they're needed, so long shows don't need to fit in memory.
+#[derive(Resource)]
+pub struct DmxPlayer {
+    path: PathBuf,
+    file: BufReader<File>,
+    /// The next record, read ahead to check its time.
+    next: Option<DmxRecord>,
+}
+
+impl DmxPlayer {
+    /// Open a recording, starting from the beginning.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("Failed to open DMX recording {path:?}"))?;
+
+        let mut this = Self { path: path.to_owned(), file: BufReader::new(file), next: None };
+        this.rewind()?;
+        Ok(this)
+    }
+
+    /// Go back to the beginning of the recording.
+    pub fn rewind(&mut self) -> Result<()> {
+        let file = File::open(&self.path)?;
+        self.file = BufReader::new(file);
+
+        let mut magic = [0u8; 8];
+        self.file
+            .read_exact(&mut magic)
+            .with_context(|| format!("{:?} is empty", self.path))?;
+        if &magic != MAGIC {
+            bail!("{:?} isn't a DMX recording, expected {MAGIC:?} but got {magic:?}", self.path);
+        }
+
+        self.next = self.read()?;
+        Ok(())
+    }
+
+    /// Whether every record has been played.
+    pub fn finished(&self) -> bool {
+        self.next.is_none()
+    }
+
+    /// Every record up to `time` since the start which hasn't been returned yet.
+    pub fn advance(&mut self, time: Duration) -> Result<Vec<DmxRecord>> {
+        let mut records = vec![];
+        while self.next.as_ref().is_some_and(|r| r.time <= time) {
+            let next = self.read()?;
+            records.extend(std::mem::replace(&mut self.next, next));
+        }
+        Ok(records)
+    }
+
+    /// Send every record up to `time` since the start to `out`, followed by a flush.
+    pub fn play(&mut self, time: Duration, out: &mut dyn DmxOutput) -> Result<()> {
+        let records = self.advance(time)?;
+        for record in &records {
+            out.send_universe(record.universe, &record.data);
+        }
+        if !records.is_empty() {
+            out.flush();
+        }
+        Ok(())
+    }
+
+    /// Read the next record, or `None` at the end of the file.
+    fn read(&mut self) -> Result<Option<DmxRecord>> {
+        let mut header = [0u8; 8];
+        match self.file.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read DMX recording {:?}", self.path)),
+        }
+
+        let [t0, t1, t2, t3, u0, u1, l0, l1] = header;
+        let len = u16::from_le_bytes([l0, l1]) as usize;
+        if len > UNIVERSE_SIZE {
+            bail!("Corrupt DMX recording {:?}: frame of {len} bytes", self.path);
+        }
+
+        let mut data = vec![0u8; len];
+        match self.file.read_exact(&mut data) {
+            Ok(()) => {}
+            // The recording was cut off mid-frame, e.g. by a crash.
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read DMX recording {:?}", self.path)),
+        }
+
+        Ok(Some(DmxRecord {
+            time: Duration::from_millis(u32::from_le_bytes([t0, t1, t2, t3]) as u64),
+            universe: u16::from_le_bytes([u0, u1]),
+            data,
+        }))
+    }
+}
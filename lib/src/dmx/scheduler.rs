@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use rtrb::{Consumer, Producer, RingBuffer};
+
+use crate::dmx::{DmxOutput, UNIVERSE_SIZE};
+use crate::prelude::*;
+
+/// How many frames can be queued before the output thread picks them up.
+const QUEUE_SIZE: usize = 64;
+
+/// Fixed-rate DMX output.
+///
+/// Frames written from the ECS are queued without locking and transmitted
+/// from a dedicated thread at a fixed rate, so output doesn't jitter with the
+/// render FPS or stop when the window stalls. Changed universes are sent on
+/// the next tick, and unchanged ones are resent every `keep_alive` so
+/// receivers don't time out.
+///
+/// Universes are sent to the backend when `DmxOutput::flush()` is called, so
+/// a frame spanning multiple universes is always transmitted together.
+#[derive(Resource)]
+pub struct DmxScheduler {
+    // Only accessed through `Mutex::get_mut()`, it's just here to make the resource `Sync`.
+    tx: Mutex<Producer<Frame>>,
+    pending: Frame,
+
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// `DmxScheduler` configuration.
+#[derive(Clone, Debug)]
+pub struct DmxSchedulerConfig {
+    /// Transmit rate in Hz. 44 Hz is the max for a full universe on a DMX512 line.
+    pub rate: f32,
+    /// How often unchanged universes are resent.
+    pub keep_alive: Duration,
+}
+
+impl Default for DmxSchedulerConfig {
+    fn default() -> Self {
+        Self { rate: 44.0, keep_alive: Duration::from_millis(800) }
+    }
+}
+
+/// The universes written between two flushes.
+#[derive(Default)]
+struct Frame {
+    universes: Vec<(u16, Vec<u8>)>,
+}
+
+/// The latest data for a universe on the output thread.
+struct Universe {
+    data: Vec<u8>,
+    dirty: bool,
+    sent: Instant,
+}
+
+impl DmxScheduler {
+    /// Constructs a new scheduler which owns `out`, using the default rate.
+    pub fn new(out: impl DmxOutput + Send + 'static) -> Self {
+        Self::with_config(out, DmxSchedulerConfig::default())
+    }
+
+    /// Constructs a new scheduler which owns `out`.
+    pub fn with_config(out: impl DmxOutput + Send + 'static, config: DmxSchedulerConfig) -> Self {
+        let (tx, rx) = RingBuffer::new(QUEUE_SIZE);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || Self::run(out, rx, config, stop))
+        };
+
+        Self { tx: Mutex::new(tx), pending: Frame::default(), stop, thread: Some(thread) }
+    }
+
+    /// Stop the output thread, dropping the backend. Nothing is sent afterwards.
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    fn run(
+        mut out: impl DmxOutput,
+        mut rx: Consumer<Frame>,
+        config: DmxSchedulerConfig,
+        stop: Arc<AtomicBool>,
+    ) {
+        let period = Duration::from_secs_f32(1.0 / config.rate.max(1.0));
+        let mut universes = BTreeMap::<u16, Universe>::new();
+
+        let mut next = Instant::now();
+        while !stop.load(Ordering::Relaxed) {
+            // Only the latest data for each universe matters.
+            while let Ok(frame) = rx.pop() {
+                for (universe, data) in frame.universes {
+                    match universes.get_mut(&universe) {
+                        Some(u) if u.data == data => {}
+                        Some(u) => {
+                            u.data = data;
+                            u.dirty = true;
+                        }
+                        None => {
+                            universes.insert(universe, Universe { data, dirty: true, sent: Instant::now() });
+                        }
+                    }
+                }
+            }
+
+            let mut sent = false;
+            for (&universe, u) in &mut universes {
+                if u.dirty || u.sent.elapsed() >= config.keep_alive {
+                    out.send_universe(universe, &u.data);
+                    u.dirty = false;
+                    u.sent = Instant::now();
+                    sent = true;
+                }
+            }
+            if sent {
+                out.flush();
+            }
+
+            // Don't try to catch up after a stall, just resume from now.
+            next += period;
+            let now = Instant::now();
+            match next.checked_duration_since(now) {
+                Some(wait) => thread::sleep(wait),
+                None => next = now,
+            }
+        }
+    }
+}
+
+impl DmxOutput for DmxScheduler {
+    fn send_universe(&mut self, universe: u16, payload: &[u8]) {
+        assert!(payload.len() <= UNIVERSE_SIZE);
+
+        let universes = &mut self.pending.universes;
+        match universes.iter_mut().find(|(u, _)| *u == universe) {
+            Some((_, data)) => {
+                data.clear();
+                data.extend_from_slice(payload);
+            }
+            None => universes.push((universe, payload.to_vec())),
+        }
+    }
+
+    /// Queue the universes sent since the last flush for the output thread.
+    fn flush(&mut self) {
+        if self.pending.universes.is_empty() {
+            return;
+        }
+
+        let frame = std::mem::take(&mut self.pending);
+        if self.tx.get_mut().unwrap().push(frame).is_err() {
+            warn!("DMX output queue is full, dropping frame");
+        }
+    }
+}
+
+impl Drop for DmxScheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// System to stop the output thread on exit, so the backend can clean up.
+pub(crate) fn stop_on_exit(mut exit: EventReader<AppExit>, scheduler: Option<ResMut<DmxScheduler>>) {
+    if exit.read().next().is_none() {
+        return;
+    }
+    if let Some(mut scheduler) = scheduler {
+        scheduler.stop();
+    }
+}
@@ -0,0 +1,37 @@
+use crate::math::Interp;
+
+/// Fraction of each strobe period the light is on for.
+const STROBE_DUTY: f32 = 0.5;
+
+/// Strobe range in Hz for fixtures whose real one isn't known. A typical figure, not measured.
+pub const NOMINAL_STROBE_HZ: (f32, f32) = (1.0, 20.0);
+
+/// Shutter state of a fixture.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Shutter {
+    #[default]
+    Open,
+    Closed,
+    /// Strobe at a rate from 0.0..1.0, slowest to fastest.
+    Strobe(f32),
+}
+
+impl Shutter {
+    /// Strobe frequency in Hz for a fixture which strobes from `min..max` Hz,
+    /// or `None` if it isn't strobing.
+    pub fn hz(self, (min, max): (f32, f32)) -> Option<f32> {
+        match self {
+            Shutter::Strobe(rate) => Some(rate.clamp(0.0, 1.0).lerp(min..max)),
+            _ => None,
+        }
+    }
+
+    /// Whether light is coming out at `t` seconds, for a fixture which strobes from `min..max` Hz.
+    pub fn is_lit(self, t: f32, hz: (f32, f32)) -> bool {
+        match self {
+            Shutter::Open => true,
+            Shutter::Closed => false,
+            Shutter::Strobe(_) => (t * self.hz(hz).unwrap()).fract() < STROBE_DUTY,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::dmx::{DmxDevice, DmxOutput, DmxPlayer, DmxReceiver, Patch, UNIVERSE_SIZE};
+use crate::prelude::*;
+
+/// Drives patched fixtures from DMX frames instead of the other way around,
+/// turning any app into a visualizer for another console, or for a recording
+/// from `DmxRecorder`.
+///
+/// Each frame, every fixture patched with a `DmxAddress` is decoded from the
+/// latest frame on its universe in `PreUpdate`, before the sim runs. Frames come
+/// from the `DmxReceiver` if there is one, a recording if played with
+/// `with_player()`, or anything else through `DmxOutput`.
+///
+/// Anything setting fixture state itself should be disabled while this is
+/// around, and there shouldn't be a `DmxScheduler`, or we'd echo what we receive.
+#[derive(Resource, Default)]
+pub struct DmxVisualizer {
+    frames: HashMap<u16, Vec<u8>>,
+    player: Option<Playback>,
+}
+
+struct Playback {
+    player: DmxPlayer,
+    /// When playback last started, since it loops at the end.
+    start: Duration,
+}
+
+impl DmxVisualizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Play a recording from `DmxRecorder`, looping at the end.
+    pub fn with_player(player: DmxPlayer) -> Self {
+        Self {
+            player: Some(Playback { player, start: Duration::ZERO }),
+            ..Default::default()
+        }
+    }
+
+    /// The latest frame on a universe, 1-indexed.
+    pub fn frame(&self, universe: u16) -> Option<&[u8]> {
+        self.frames.get(&universe).map(|frame| frame.as_slice())
+    }
+}
+
+impl DmxOutput for DmxVisualizer {
+    fn send_universe(&mut self, universe: u16, payload: &[u8]) {
+        assert!(payload.len() <= UNIVERSE_SIZE);
+
+        let frame = self.frames.entry(universe).or_default();
+        frame.clear();
+        frame.extend_from_slice(payload);
+    }
+}
+
+/// System to pull in frames from the `DmxReceiver` and recording.
+pub fn receive(
+    mut visualizer: ResMut<DmxVisualizer>,
+    receiver: Option<Res<DmxReceiver>>,
+    time: Res<Time>,
+) -> Result {
+    if let Some(receiver) = receiver {
+        for frame in receiver.frames() {
+            visualizer.send_universe(frame.universe, &frame.data);
+        }
+    }
+
+    let DmxVisualizer { frames, player } = &mut *visualizer;
+    if let Some(playback) = player {
+        if playback.player.finished() {
+            playback.player.rewind()?;
+            playback.start = time.elapsed();
+        }
+        for record in playback.player.advance(time.elapsed() - playback.start)? {
+            frames.insert(record.universe, record.data);
+        }
+    }
+    Ok(())
+}
+
+/// System to decode every patched `DmxDevice` from the latest frames.
+pub fn decode(
+    visualizer: Res<DmxVisualizer>,
+    patch: Res<Patch>,
+    mut devices: Query<One<&mut dyn DmxDevice>>,
+) {
+    for (entity, at) in patch.iter() {
+        let Some(frame) = visualizer.frame(at.universe) else {
+            continue;
+        };
+        let Ok(mut device) = devices.get_mut(entity) else {
+            continue;
+        };
+
+        // Short frames are padded with zeros, same as a real fixture would see.
+        let mut buf = [0u8; UNIVERSE_SIZE];
+        let channels = device.channels();
+        let end = frame.len().min(at.address + channels);
+        if at.address < end {
+            buf[..end - at.address].copy_from_slice(&frame[at.address..end]);
+        }
+        device.decode(&buf[..channels]);
+    }
+}
@@ -1,17 +1,24 @@
-use std::net::{IpAddr, SocketAddr};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
-use anyhow::{Result, anyhow};
+use anyhow::{Result, anyhow, bail};
 use sacn::packet::ACN_SDT_MULTICAST_PORT;
 use sacn::source::SacnSource;
 
+use crate::dmx::UNIVERSE_SIZE;
 use crate::prelude::*;
 
 /// The default E1.31 port.
-const DEFAULT_PORT: u16 = ACN_SDT_MULTICAST_PORT;
+pub(crate) const DEFAULT_PORT: u16 = ACN_SDT_MULTICAST_PORT;
 
 /// The default DMX universe to use, 1-indexed.
 const DEFAULT_DMX_UNIVERSE: u16 = 1;
 
+/// The default E1.31 priority.
+const DEFAULT_PRIORITY: u8 = 100;
+/// The maximum E1.31 priority.
+const MAX_PRIORITY: u8 = 200;
+
 /// E1.31 (aka Streaming ACN) sender.
 ///
 /// # Protocol
@@ -20,32 +27,235 @@ const DEFAULT_DMX_UNIVERSE: u16 = 1;
 /// used in the lighting industry, and has excellent library support on various
 /// platforms including microcontrollers.
 ///
+/// Streams are terminated on `AppExit` or when dropped, so that receivers can
+/// release our universes to a backup source right away instead of holding the
+/// last frame until they time out.
+///
 /// See <https://wiki.openlighting.org/index.php/E1.31>
 #[derive(Resource)]
 pub struct E131 {
     src: SacnSource,
     dest: IpAddr,
+    /// Universes registered with `src`, 1-indexed.
+    universes: Vec<u16>,
+    config: E131Config,
+    terminated: bool,
+}
+
+/// E1.31 sender configuration.
+#[derive(Clone, Debug)]
+pub struct E131Config {
+    /// Universes to register up front, 1-indexed.
+    pub universes: Vec<u16>,
+    /// Priority from 0..=200 for universes not in `priorities`.
+    /// Receivers follow the highest priority source for each universe.
+    pub priority: u8,
+    /// Per-universe priority overrides.
+    pub priorities: HashMap<u16, u8>,
+    /// Universe to send synchronization packets on. When set, receivers hold
+    /// new frames until the next `DmxOutput::flush()`, so multi-universe
+    /// updates land at the same time.
+    pub sync_universe: Option<u16>,
+}
+
+impl Default for E131Config {
+    fn default() -> Self {
+        Self {
+            universes: vec![DEFAULT_DMX_UNIVERSE],
+            priority: DEFAULT_PRIORITY,
+            priorities: HashMap::new(),
+            sync_universe: None,
+        }
+    }
 }
 
 impl E131 {
-    /// Constructs a new E1.31 sender.
+    /// Constructs a new E1.31 sender on the default universe.
     pub fn new(dest_ip: &str) -> Result<Self> {
+        Self::with_config(dest_ip, E131Config::default())
+    }
+
+    /// Constructs a new E1.31 sender on the given universes, 1-indexed.
+    pub fn with_universes(dest_ip: &str, universes: &[u16]) -> Result<Self> {
+        Self::with_config(dest_ip, E131Config { universes: universes.to_vec(), ..Default::default() })
+    }
+
+    /// Constructs a new E1.31 sender with the given priorities and synchronization.
+    pub fn with_config(dest_ip: &str, config: E131Config) -> Result<Self> {
         let src_addr = SocketAddr::new("0.0.0.0".parse()?, 0);
         let dest = dest_ip.parse().with_context(|| format!("failed to parse ip: {dest_ip:?}"))?;
 
-        let mut src = SacnSource::with_ip("stagebridge", src_addr).map_err(|e| anyhow!("{e}"))?;
-        src.register_universe(DEFAULT_DMX_UNIVERSE).unwrap();
+        let priorities = config.priorities.values();
+        if let Some(priority) = priorities.chain([&config.priority]).find(|&&p| p > MAX_PRIORITY) {
+            bail!("E1.31 priority {priority} is out of range 0..={MAX_PRIORITY}");
+        }
+
+        let src = SacnSource::with_ip("stagebridge", src_addr).map_err(|e| anyhow!("{e}"))?;
+
+        let universes = config.universes.clone();
+        let sync_universe = config.sync_universe;
+
+        let mut this = Self { src, dest, universes: vec![], config, terminated: false };
+        for universe in universes.into_iter().chain(sync_universe) {
+            this.register(universe)?;
+        }
+        Ok(this)
+    }
+
+    /// Register a universe to send on, 1-indexed. Does nothing if it's already registered.
+    pub fn register(&mut self, universe: u16) -> Result<()> {
+        if !self.universes.contains(&universe) {
+            self.src
+                .register_universe(universe)
+                .map_err(|e| anyhow!("failed to register E1.31 universe {universe}: {e}"))?;
+            self.universes.push(universe);
+        }
+        Ok(())
+    }
+
+    /// The registered universes, 1-indexed.
+    pub fn universes(&self) -> &[u16] {
+        &self.universes
+    }
+
+    /// The priority a universe is sent with.
+    pub fn priority(&self, universe: u16) -> u8 {
+        self.config.priorities.get(&universe).copied().unwrap_or(self.config.priority)
+    }
 
-        Ok(Self { src, dest })
+    /// Change the priority of a universe, from 0..=200.
+    pub fn set_priority(&mut self, universe: u16, priority: u8) -> Result<()> {
+        if priority > MAX_PRIORITY {
+            bail!("E1.31 priority {priority} is out of range 0..={MAX_PRIORITY}");
+        }
+        self.config.priorities.insert(universe, priority);
+        Ok(())
     }
 
-    /// Send a packet of up to 512 DMX channels to the given destination.
+    /// Send a packet of up to 512 DMX channels on the default universe.
     pub fn send(&mut self, payload: &[u8]) {
-        assert!(payload.len() <= 512);
+        self.send_universe(DEFAULT_DMX_UNIVERSE, payload);
+    }
+
+    /// Terminate the streams on all registered universes. Nothing is sent afterwards.
+    pub fn terminate(&mut self) {
+        if self.terminated {
+            return;
+        }
+        self.terminated = true;
+
+        for &universe in &self.universes {
+            if let Err(e) = self.src.terminate_stream(universe, 0) {
+                error!("Failed to terminate E1.31 universe {universe}: {e}");
+            }
+        }
+    }
+}
+
+impl DmxOutput for E131 {
+    /// Unregistered universes are registered on first use.
+    fn send_universe(&mut self, universe: u16, payload: &[u8]) {
+        assert!(payload.len() <= UNIVERSE_SIZE);
+
+        if self.terminated {
+            return;
+        }
+
+        if let Err(e) = self.register(universe) {
+            error!("{e}");
+            return;
+        }
+
+        let dest = SocketAddr::new(self.dest, DEFAULT_PORT);
+        let priority = self.priority(universe);
+        let sync = self.config.sync_universe;
+        if let Err(e) = self.src.send(&[universe], payload, Some(priority), Some(dest), sync) {
+            error!("Failed to send E1.31 universe {universe} to {dest}: {e}");
+        }
+    }
+
+    /// Sends a synchronization packet if `sync_universe` is set.
+    fn flush(&mut self) {
+        let Some(sync) = self.config.sync_universe else {
+            return;
+        };
+        if self.terminated {
+            return;
+        }
 
-        let dest = SocketAddr::new(self.dest.clone(), DEFAULT_PORT);
-        if let Err(e) = self.src.send(&[DEFAULT_DMX_UNIVERSE], payload, None, Some(dest), None) {
-            error!("Failed to send E1.31 to {dest}: {e}");
+        let dest = SocketAddr::new(self.dest, DEFAULT_PORT);
+        if let Err(e) = self.src.send_sync_packet(sync, Some(dest)) {
+            error!("Failed to send E1.31 sync on universe {sync} to {dest}: {e}");
         }
     }
 }
+
+impl Drop for E131 {
+    fn drop(&mut self) {
+        self.terminate();
+    }
+}
+
+/// System to terminate the E1.31 streams on exit.
+pub(crate) fn terminate_on_exit(mut exit: EventReader<AppExit>, e131: Option<ResMut<E131>>) {
+    if exit.read().next().is_none() {
+        return;
+    }
+    if let Some(mut e131) = e131 {
+        e131.terminate();
+    }
+}
+
+/// The multicast group for a 1-indexed universe.
+pub(crate) fn multicast_addr(universe: u16) -> Ipv4Addr {
+    let [hi, lo] = universe.to_be_bytes();
+    Ipv4Addr::new(239, 255, hi, lo)
+}
+
+/// The interesting parts of an E1.31 data packet.
+pub(crate) struct DataPacket<'a> {
+    pub universe: u16,
+    pub priority: u8,
+    /// Whether the source is terminating the stream.
+    pub terminated: bool,
+    /// Start code followed by up to 512 channels.
+    pub data: &'a [u8],
+}
+
+/// Parse an E1.31 data packet. Ignores sync, discovery, and preview packets.
+///
+/// See section 4 of ANSI E1.31 for the layout.
+pub(crate) fn parse_data(packet: &[u8]) -> Option<DataPacket<'_>> {
+    const ACN_ID: &[u8; 12] = b"ASC-E1.17\0\0\0";
+    const VECTOR_ROOT_E131_DATA: u32 = 0x4;
+    const VECTOR_E131_DATA_PACKET: u32 = 0x2;
+    const VECTOR_DMP_SET_PROPERTY: u8 = 0x2;
+
+    const OPTION_PREVIEW: u8 = 0x80;
+    const OPTION_TERMINATED: u8 = 0x40;
+
+    let u16_at = |i: usize| u16::from_be_bytes([packet[i], packet[i + 1]]);
+    let u32_at = |i: usize| u32::from_be_bytes([packet[i], packet[i + 1], packet[i + 2], packet[i + 3]]);
+
+    if packet.len() < 126
+        || &packet[4..16] != ACN_ID
+        || u32_at(18) != VECTOR_ROOT_E131_DATA
+        || u32_at(40) != VECTOR_E131_DATA_PACKET
+        || packet[117] != VECTOR_DMP_SET_PROPERTY
+    {
+        return None;
+    }
+
+    let options = packet[112];
+    if options & OPTION_PREVIEW != 0 {
+        return None;
+    }
+
+    let count = u16_at(123) as usize;
+    Some(DataPacket {
+        universe: u16_at(113),
+        priority: packet[108],
+        terminated: options & OPTION_TERMINATED != 0,
+        data: &packet[125..(125 + count).min(packet.len())],
+    })
+}
@@ -0,0 +1,93 @@
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serialport::SerialPort;
+
+use crate::dmx::UNIVERSE_SIZE;
+use crate::prelude::*;
+
+/// The default DMX universe to output, 1-indexed.
+const DEFAULT_DMX_UNIVERSE: u16 = 1;
+
+const START_OF_MESSAGE: u8 = 0x7E;
+const END_OF_MESSAGE: u8 = 0xE7;
+/// "Output Only Send DMX Packet Request"
+const LABEL_SEND_DMX: u8 = 6;
+/// The widget wants at least 24 channels, plus the start code.
+const MIN_DATA_SIZE: usize = 25;
+
+/// Enttec DMX USB Pro sender.
+///
+/// # Protocol
+///
+/// The widget shows up as a USB serial port, and takes messages framed as
+/// `0x7E, label, length (LE u16), data, 0xE7`. Label 6 outputs a DMX frame,
+/// where the data is the start code followed by up to 512 channels.
+///
+/// The widget has a single DMX port, so only one universe is sent and the rest are ignored.
+///
+/// See <https://cdn.enttec.com/pdf/assets/70304/70304_DMX_USB_PRO_API.pdf>
+#[derive(Resource)]
+pub struct EnttecPro {
+    port: Mutex<Box<dyn SerialPort>>,
+    universe: u16,
+}
+
+impl EnttecPro {
+    /// Constructs a new sender on the serial device at `path`, e.g. `/dev/ttyUSB0`,
+    /// outputting the default universe.
+    pub fn new(path: &str) -> Result<Self> {
+        Self::with_universe(path, DEFAULT_DMX_UNIVERSE)
+    }
+
+    /// Constructs a new sender on the serial device at `path`, outputting the given universe.
+    pub fn with_universe(path: &str, universe: u16) -> Result<Self> {
+        // The widget is USB, so the baud rate is ignored.
+        let port = serialport::new(path, 115_200)
+            .timeout(Duration::from_millis(100))
+            .open()
+            .with_context(|| format!("Failed to open Enttec DMX USB Pro at {path:?}"))?;
+        Ok(Self::with_port(port, universe))
+    }
+
+    /// Constructs a new sender on an already open serial port.
+    pub fn with_port(port: Box<dyn SerialPort>, universe: u16) -> Self {
+        Self { port: Mutex::new(port), universe }
+    }
+
+    /// The universe being output, 1-indexed.
+    pub fn universe(&self) -> u16 {
+        self.universe
+    }
+}
+
+impl DmxOutput for EnttecPro {
+    fn send_universe(&mut self, universe: u16, payload: &[u8]) {
+        assert!(payload.len() <= UNIVERSE_SIZE);
+
+        if universe != self.universe {
+            return;
+        }
+
+        let port = self.port.get_mut().unwrap();
+        if let Err(e) = port.write_all(&encode_dmx(payload)) {
+            error!("Failed to send DMX universe {universe} to Enttec DMX USB Pro: {e}");
+        }
+    }
+}
+
+/// Encode a "Send DMX Packet" message. `payload` includes the start code.
+fn encode_dmx(payload: &[u8]) -> Vec<u8> {
+    let len = payload.len().max(MIN_DATA_SIZE);
+
+    let mut msg = Vec::with_capacity(len + 5);
+    msg.push(START_OF_MESSAGE);
+    msg.push(LABEL_SEND_DMX);
+    msg.extend_from_slice(&(len as u16).to_le_bytes());
+    msg.extend_from_slice(payload);
+    msg.resize(4 + len, 0);
+    msg.push(END_OF_MESSAGE);
+    msg
+}
@@ -0,0 +1,191 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+
+use super::{LaserFrame, LaserPoint};
+use crate::prelude::*;
+
+/// Start of every section header.
+const MAGIC: &[u8; 4] = b"ILDA";
+const HEADER_SIZE: usize = 32;
+
+/// Status bits of a point.
+const LAST_POINT: u8 = 1 << 7;
+const BLANK: u8 = 1 << 6;
+
+/// Palette for the indexed formats until a file sets its own.
+#[rustfmt::skip]
+const DEFAULT_PALETTE: [(u8, u8, u8); 64] = [
+    (255, 0, 0),     (255, 16, 0),    (255, 32, 0),    (255, 48, 0),
+    (255, 64, 0),    (255, 80, 0),    (255, 96, 0),    (255, 112, 0),
+    (255, 128, 0),   (255, 144, 0),   (255, 160, 0),   (255, 176, 0),
+    (255, 192, 0),   (255, 208, 0),   (255, 224, 0),   (255, 240, 0),
+    (255, 255, 0),   (224, 255, 0),   (192, 255, 0),   (160, 255, 0),
+    (128, 255, 0),   (96, 255, 0),    (64, 255, 0),    (32, 255, 0),
+    (0, 255, 0),     (0, 255, 36),    (0, 255, 73),    (0, 255, 109),
+    (0, 255, 146),   (0, 255, 182),   (0, 255, 219),   (0, 255, 255),
+    (0, 227, 255),   (0, 198, 255),   (0, 170, 255),   (0, 142, 255),
+    (0, 113, 255),   (0, 85, 255),    (0, 56, 255),    (0, 28, 255),
+    (0, 0, 255),     (32, 0, 255),    (64, 0, 255),    (96, 0, 255),
+    (128, 0, 255),   (160, 0, 255),   (192, 0, 255),   (224, 0, 255),
+    (255, 0, 255),   (255, 32, 255),  (255, 64, 255),  (255, 96, 255),
+    (255, 128, 255), (255, 160, 255), (255, 192, 255), (255, 224, 255),
+    (255, 255, 255), (255, 224, 224), (255, 192, 192), (255, 160, 160),
+    (255, 128, 128), (255, 96, 96),   (255, 64, 64),   (255, 32, 32),
+];
+
+/// Parse the frames of an ILDA file.
+///
+/// Supports the indexed formats 0 and 1, palettes in format 2, and the true
+/// color formats 4 and 5. Z coordinates of the 3D formats are dropped.
+pub fn parse_ilda(data: &[u8]) -> Result<Vec<LaserFrame>> {
+    let rgb = |r: u8, g: u8, b: u8| Rgb(r.float(), g.float(), b.float());
+    let mut palette = DEFAULT_PALETTE.map(|(r, g, b)| rgb(r, g, b)).to_vec();
+
+    let mut frames = vec![];
+    let mut rest = data;
+    // Files are supposed to end with an empty section, but not all of them do.
+    while !rest.is_empty() {
+        let offset = data.len() - rest.len();
+        let Some((header, body)) = rest.split_first_chunk::<HEADER_SIZE>() else {
+            bail!("Truncated ILDA header at byte {offset}");
+        };
+        if &header[..4] != MAGIC {
+            bail!("Expected an ILDA header at byte {offset}, got {:?}", &header[..4]);
+        }
+
+        let format = header[7];
+        let count = u16::from_be_bytes([header[24], header[25]]) as usize;
+        if count == 0 {
+            break;
+        }
+
+        let size = match format {
+            0 => 8,
+            1 => 6,
+            2 => 3,
+            4 => 10,
+            5 => 8,
+            _ => bail!("Unsupported ILDA format {format} at byte {offset}"),
+        };
+        let Some(records) = body.get(..count * size) else {
+            bail!("Truncated ILDA section at byte {offset}: expected {count} records of {size} bytes");
+        };
+        rest = &body[count * size..];
+
+        if format == 2 {
+            palette = records.chunks_exact(3).map(|c| rgb(c[0], c[1], c[2])).collect();
+            continue;
+        }
+
+        let points = records.chunks_exact(size).map(|r| {
+            let pos = Vec2::new(coord(r[0], r[1]), coord(r[2], r[3]));
+            // Skip over z in the 3D formats.
+            let r = if matches!(format, 0 | 4) { &r[6..] } else { &r[4..] };
+            let color = match format {
+                0 | 1 => palette.get(r[1] as usize).copied().unwrap_or(Rgb::WHITE),
+                _ => rgb(r[3], r[2], r[1]),
+            };
+            LaserPoint { pos, color, blank: r[0] & BLANK != 0 }
+        });
+        frames.push(LaserFrame { points: points.collect() });
+    }
+
+    Ok(frames)
+}
+
+/// Load the frames of an ILDA file.
+pub fn load_ilda(path: impl AsRef<Path>) -> Result<Vec<LaserFrame>> {
+    let path = path.as_ref();
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {path:?}"))?;
+    parse_ilda(&data).with_context(|| format!("Failed to parse ILDA file {path:?}"))
+}
+
+/// Write frames as an ILDA file, in the 2D true color format 5.
+///
+/// A header with no points marks the end of the file, so empty frames are
+/// written as a single blanked point rather than dropping them and throwing off
+/// the timing of the frames after.
+pub fn write_ilda(frames: &[LaserFrame]) -> Vec<u8> {
+    let mut data = vec![];
+    let header = |data: &mut Vec<u8>, count: usize, number: usize| {
+        data.extend(MAGIC);
+        data.extend([0, 0, 0, 5]);
+        data.extend(b"ravy\0\0\0\0"); // name
+        data.extend(b"ravy\0\0\0\0"); // company
+        data.extend((count as u16).to_be_bytes());
+        data.extend((number as u16).to_be_bytes());
+        data.extend((frames.len() as u16).to_be_bytes());
+        data.extend([0, 0]); // projector, reserved
+    };
+
+    let blank = [LaserPoint { pos: Vec2::ZERO, color: Rgb::BLACK, blank: true }];
+    for (i, frame) in frames.iter().enumerate() {
+        let points = if frame.points.is_empty() { &blank[..] } else { &frame.points[..] };
+        let count = points.len();
+        assert!(
+            count <= u16::MAX as usize,
+            "ILDA frames can't have more than 65535 points, got {count}"
+        );
+
+        header(&mut data, count, i);
+        for (j, point) in points.iter().enumerate() {
+            let coord = |v: f32| ((v.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16).to_be_bytes();
+            let last = if j == count - 1 { LAST_POINT } else { 0 };
+            let blank = if point.blank { BLANK } else { 0 };
+            let Rgb(r, g, b) = point.color;
+
+            data.extend(coord(point.pos.x));
+            data.extend(coord(point.pos.y));
+            data.extend([last | blank, b.byte(), g.byte(), r.byte()]);
+        }
+    }
+    header(&mut data, 0, frames.len());
+
+    data
+}
+
+/// Convert a big endian coordinate to -1.0..1.0.
+fn coord(hi: u8, lo: u8) -> f32 {
+    (i16::from_be_bytes([hi, lo]) as f32 / i16::MAX as f32).max(-1.0)
+}
+
+/// Plays ILDA frames into the entity's `LaserFrame` at a fixed frame rate, looping at the end.
+#[derive(Component)]
+#[require(LaserFrame)]
+pub struct IldaPlayer {
+    frames: Vec<LaserFrame>,
+    pub fps: f32,
+    time: f32,
+    /// Index of the frame in `LaserFrame`, to only update it on changes.
+    shown: Option<usize>,
+}
+
+impl IldaPlayer {
+    pub fn new(frames: Vec<LaserFrame>, fps: f32) -> Self {
+        Self { frames, fps, time: 0.0, shown: None }
+    }
+
+    /// Load an ILDA file, see `load_ilda()`.
+    pub fn load(path: impl AsRef<Path>, fps: f32) -> Result<Self> {
+        Ok(Self::new(load_ilda(path)?, fps))
+    }
+
+    /// Index of the current frame.
+    pub fn index(&self) -> usize {
+        (self.time * self.fps) as usize % self.frames.len().max(1)
+    }
+}
+
+/// System to advance `IldaPlayer`s.
+pub fn play(mut players: Query<(&mut IldaPlayer, &mut LaserFrame)>, time: Res<Time>) {
+    for (mut player, mut frame) in &mut players {
+        player.time += time.delta_secs();
+
+        let i = player.index();
+        if player.shown != Some(i) && i < player.frames.len() {
+            *frame = player.frames[i].clone();
+            player.shown = Some(i);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Vector laser output, for ILDA-capable lasers driven point by point rather than over DMX.
+
+use crate::prelude::*;
+
+mod ilda;
+pub use ilda::{IldaPlayer, load_ilda, parse_ilda, write_ilda};
+
+pub struct LaserPlugin;
+impl Plugin for LaserPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, ilda::play).add_systems(PostUpdate, send);
+    }
+}
+
+/// A single point of a laser frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LaserPoint {
+    /// Position from -1.0..1.0 on each axis, with y up.
+    pub pos: Vec2,
+    pub color: Rgb,
+    /// Whether the beam is off while moving to this point.
+    pub blank: bool,
+}
+
+/// The points a laser scans, in order, repeated until the next frame.
+///
+/// Spawn it alongside a `Transform` to draw it in the 3D sim, pointing down the
+/// entity's -Z, and a `LaserDac` to send it to a real laser.
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct LaserFrame {
+    pub points: Vec<LaserPoint>,
+}
+
+impl LaserFrame {
+    /// Segments which are drawn, skipping the blanked moves between them.
+    pub fn segments(&self) -> impl Iterator<Item = (LaserPoint, LaserPoint)> + '_ {
+        self.points.windows(2).map(|w| (w[0], w[1])).filter(|(_, b)| !b.blank)
+    }
+}
+
+/// A laser DAC backend, e.g. an Ether Dream.
+pub trait LaserOutput {
+    /// Send a frame, which the DAC scans repeatedly until the next one.
+    fn send_frame(&mut self, frame: &LaserFrame);
+}
+
+impl<T: LaserOutput + ?Sized> LaserOutput for &mut T {
+    fn send_frame(&mut self, frame: &LaserFrame) {
+        (**self).send_frame(frame);
+    }
+}
+
+impl<T: LaserOutput + ?Sized> LaserOutput for Box<T> {
+    fn send_frame(&mut self, frame: &LaserFrame) {
+        (**self).send_frame(frame);
+    }
+}
+
+/// Send to both outputs.
+impl<A: LaserOutput, B: LaserOutput> LaserOutput for (A, B) {
+    fn send_frame(&mut self, frame: &LaserFrame) {
+        self.0.send_frame(frame);
+        self.1.send_frame(frame);
+    }
+}
+
+/// Sends an entity's `LaserFrame` to a DAC whenever it changes.
+#[derive(Component)]
+pub struct LaserDac {
+    out: Box<dyn LaserOutput + Send + Sync>,
+}
+
+impl LaserDac {
+    pub fn new(out: impl LaserOutput + Send + Sync + 'static) -> Self {
+        Self { out: Box::new(out) }
+    }
+}
+
+/// System to send changed frames to their DACs.
+pub fn send(mut lasers: Query<(&LaserFrame, &mut LaserDac), Or<(Changed<LaserFrame>, Added<LaserDac>)>>) {
+    for (frame, mut dac) in &mut lasers {
+        dac.out.send_frame(frame);
+    }
+}
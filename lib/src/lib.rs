@@ -3,11 +3,14 @@
 #![allow(clippy::eq_op)]
 #![allow(mixed_script_confusables)]
 
+mod artnet;
 mod audio;
 mod color;
 pub mod dmx;
 mod e131;
+mod enttec;
 mod gltf;
+pub mod laser;
 pub mod lights;
 pub mod math;
 pub mod midi;
@@ -27,13 +30,19 @@ pub mod prelude {
     pub use bevy_trait_query::{One, RegisterExt};
     pub use dyn_clone::{DynClone, clone_trait_object};
 
+    pub use crate::artnet::{ArtNet, ArtNetNode};
     pub use crate::audio::*;
     pub use crate::color::*;
-    pub use crate::dmx::{DmxDevice, DmxUniverse};
-    pub use crate::e131::E131;
+    pub use crate::dmx::{
+        DmxAddress, DmxDecode, DmxDevice, DmxEncode, DmxMerge, DmxOutput, DmxPlayer, DmxReceiver,
+        DmxRecorder, DmxScheduler, DmxUniverse, DmxVisualizer, Patch, Shutter,
+    };
+    pub use crate::e131::{E131, E131Config};
+    pub use crate::enttec::EnttecPro;
     pub use crate::gltf::*;
+    pub use crate::laser::{IldaPlayer, LaserDac, LaserFrame, LaserOutput, LaserPoint};
     pub use crate::math::{self, Axis, Ease, *};
-    pub use crate::midi::{Midi, MidiDevice};
+    pub use crate::midi::{Midi, MidiConnection, MidiDevice, MidiInput, MidiPlugin, MidiStatus};
     pub use crate::osc::*;
     pub use crate::plugin::RavyPlugin;
     pub use crate::synesthesia::Synesthesia;
@@ -9,6 +9,7 @@ use crate::prelude::*;
 pub struct SaberSpot {
     pub color: Rgbw,
     pub alpha: f32,
+    pub shutter: Shutter,
 }
 
 impl SpotDevice for SaberSpot {
@@ -32,7 +33,10 @@ impl SpotDevice for SaberSpot {
     }
 
     fn color(&self) -> Rgbw {
-        self.color
+        self.color * self.alpha
+    }
+    fn shutter(&self) -> Shutter {
+        self.shutter
     }
 }
 
@@ -42,20 +46,34 @@ impl DmxDevice for SaberSpot {
     }
 
     fn encode(&self, dmx: &mut [u8]) {
-        dmx.fill(0);
+        dmx[..self.channels()].fill(0);
 
         let Rgbw(r, g, b, w) = self.color;
         dmx[0] = r.byte();
         dmx[1] = g.byte();
         dmx[2] = b.byte();
         dmx[3] = w.byte();
-        dmx[4] = 255; // strobe: led on
+        dmx[4] = match self.shutter {
+            Shutter::Open => 255,
+            Shutter::Closed => 0,
+            Shutter::Strobe(rate) => rate.clamp(0.0, 1.0).lerp(64..95) as u8,
+        };
         dmx[5] = self.alpha.byte();
     }
+
+    fn decode(&mut self, dmx: &[u8]) {
+        self.color = Rgbw(dmx[0].float(), dmx[1].float(), dmx[2].float(), dmx[3].float());
+        self.shutter = match dmx[4] {
+            0..32 => Shutter::Closed,
+            b @ 64..96 => Shutter::Strobe((b - 64) as f32 / 31.0),
+            _ => Shutter::Open,
+        };
+        self.alpha = dmx[5].float();
+    }
 }
 
 impl Default for SaberSpot {
     fn default() -> Self {
-        Self { color: Rgbw::BLACK, alpha: 1.0 }
+        Self { color: Rgbw::BLACK, alpha: 1.0, shutter: Shutter::Open }
     }
 }
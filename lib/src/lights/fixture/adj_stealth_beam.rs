@@ -12,7 +12,7 @@ pub struct StealthBeam {
     pub yaw: f32,
     pub color: Rgbw,
     pub alpha: f32,
-    pub strobe: f32,
+    pub shutter: Shutter,
 }
 
 impl MovingHeadDevice for StealthBeam {
@@ -56,6 +56,9 @@ impl MovingHeadDevice for StealthBeam {
             linear_gain: 1.5,
         }
     }
+    fn fine_channels(&self) -> bool {
+        true
+    }
 
     fn pitch(&self) -> f32 {
         self.pitch
@@ -64,7 +67,10 @@ impl MovingHeadDevice for StealthBeam {
         self.yaw
     }
     fn color(&self) -> Rgbw {
-        self.color
+        self.color * self.alpha
+    }
+    fn shutter(&self) -> Shutter {
+        self.shutter
     }
 }
 
@@ -74,10 +80,10 @@ impl DmxDevice for StealthBeam {
     }
 
     fn encode(&self, dmx: &mut [u8]) {
-        dmx.fill(0);
+        dmx[..self.channels()].fill(0);
 
-        dmx[0] = self.yaw.byte();
-        dmx[2] = self.pitch.byte();
+        [dmx[0], dmx[1]] = self.yaw.word().to_be_bytes();
+        [dmx[2], dmx[3]] = self.pitch.word().to_be_bytes();
 
         let Rgbw(r, g, b, w) = self.color;
         dmx[4] = r.byte();
@@ -86,12 +92,34 @@ impl DmxDevice for StealthBeam {
         dmx[7] = w.byte();
 
         dmx[9] = self.alpha.byte();
-        dmx[10] = 255; // strobe: shutter open
+        dmx[10] = match self.shutter {
+            Shutter::Open => 255,
+            Shutter::Closed => 0,
+            Shutter::Strobe(rate) => rate.clamp(0.0, 1.0).lerp(64..95) as u8,
+        };
+    }
+
+    fn decode(&mut self, dmx: &[u8]) {
+        self.yaw = u16::from_be_bytes([dmx[0], dmx[1]]).float();
+        self.pitch = u16::from_be_bytes([dmx[2], dmx[3]]).float();
+        self.color = Rgbw(dmx[4].float(), dmx[5].float(), dmx[6].float(), dmx[7].float());
+        self.alpha = dmx[9].float();
+        self.shutter = match dmx[10] {
+            0..32 => Shutter::Closed,
+            b @ 64..96 => Shutter::Strobe((b - 64) as f32 / 31.0),
+            _ => Shutter::Open,
+        };
     }
 }
 
 impl Default for StealthBeam {
     fn default() -> Self {
-        Self { pitch: 0.5, yaw: 0.0, strobe: 0.0, color: Rgbw::BLACK, alpha: 1.0 }
+        Self {
+            pitch: 0.5,
+            yaw: 0.0,
+            shutter: Shutter::Open,
+            color: Rgbw::BLACK,
+            alpha: 1.0,
+        }
     }
 }
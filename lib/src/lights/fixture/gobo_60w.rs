@@ -0,0 +1,113 @@
+//! 60W RGBW moving head
+//!
+//! <TODO: amazon link>
+
+use crate::dmx::device::gobo_60w::Gobo;
+use crate::lights::{GoboDevice, GoboPattern, MovingHeadDevice};
+use crate::prelude::*;
+use crate::sim::motor::MotorDynamics;
+
+// The real wheels haven't been checked against the manual, so these are stand-ins for the sim,
+// spread evenly across each wheel's channel. What's sent over DMX is the raw channel value.
+
+/// Color wheel, in order.
+const COLORS: [Rgb; 8] = [
+    Rgb::WHITE,
+    Rgb::RED,
+    Rgb::LIME,
+    Rgb::BLUE,
+    Rgb::YELLOW,
+    Rgb::ORANGE,
+    Rgb::CYAN,
+    Rgb::PINK,
+];
+
+/// Gobo wheel, in order.
+const GOBOS: [GoboPattern; 8] = [
+    GoboPattern::Open,
+    GoboPattern::Dots(6),
+    GoboPattern::Star(5),
+    GoboPattern::Spokes(8),
+    GoboPattern::Bars(4),
+    GoboPattern::Rings(3),
+    GoboPattern::Spiral(3),
+    GoboPattern::Dots(3),
+];
+
+/// Which of `n` evenly spaced slots a channel value lands on.
+fn slot(value: f32, n: usize) -> usize {
+    ((value.clamp(0.0, 1.0) * n as f32) as usize).min(n - 1)
+}
+
+impl GoboDevice for Gobo {
+    fn gobos(&self) -> &'static [GoboPattern] {
+        &GOBOS
+    }
+    fn gobo(&self) -> usize {
+        slot(self.pattern, GOBOS.len())
+    }
+
+    fn colors(&self) -> &'static [Rgb] {
+        &COLORS
+    }
+    fn color_slot(&self) -> usize {
+        slot(self.color, COLORS.len())
+    }
+}
+
+// There's no model for this one yet, so it borrows the Stealth Beam's.
+impl MovingHeadDevice for Gobo {
+    fn name(&self) -> &'static str {
+        "60W Gobo"
+    }
+    fn intensity(&self) -> f32 {
+        5_000_000.0
+    }
+    fn range(&self) -> f32 {
+        10.0
+    }
+    fn beam_angle(&self) -> f32 {
+        12.0
+    }
+    fn model(&self) -> &'static [u8] {
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/fixtures/ADJ_Stealth_Beam.glb"))
+    }
+    fn model_path(&self) -> &'static str {
+        "fixtures/ADJ_Stealth_Beam.glb"
+    }
+    fn pitch_dynamics(&self) -> MotorDynamics {
+        MotorDynamics {
+            θ_min: 0.0,
+            θ_max: 180.0,
+            v_max: 300.0,
+            a_max: 1_500.0,
+            j_max: 100_000.0,
+            linear_threshold: 15.0,
+            linear_gain: 5.0,
+        }
+    }
+    fn yaw_dynamics(&self) -> MotorDynamics {
+        MotorDynamics {
+            θ_min: 0.0,
+            θ_max: -540.0,
+            v_max: 300.0,
+            a_max: 1_000.0,
+            j_max: 100_000.0,
+            linear_threshold: 80.0,
+            linear_gain: 1.5,
+        }
+    }
+
+    fn pitch(&self) -> f32 {
+        self.tilt
+    }
+    fn yaw(&self) -> f32 {
+        self.pan
+    }
+    fn color(&self) -> Rgbw {
+        Rgbw::from(self.wheel_color()) * self.alpha
+    }
+    fn shutter(&self) -> Shutter {
+        if self.strobe > 0.0 { Shutter::Strobe(self.strobe) } else { Shutter::Open }
+    }
+}
@@ -3,3 +3,6 @@ pub use adj_stealth_beam::StealthBeam;
 
 mod adj_saber_spot;
 pub use adj_saber_spot::SaberSpot;
+
+// The fixture itself is in `dmx::device`, this is just its sim model.
+mod gobo_60w;
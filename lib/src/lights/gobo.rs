@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::math::Affine2;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::prelude::*;
+
+/// Most prism facets that are simulated.
+const MAX_FACETS: usize = 8;
+/// Resolution of the procedural gobo textures.
+const GOBO_SIZE: u32 = 256;
+
+/// A fixture with a gobo wheel, and optionally a color wheel and prism.
+///
+/// Simulated by projecting the gobo onto the floor from the fixture's `SpotLight`,
+/// so it needs to be a `MovingHeadDevice` or `SpotDevice` as well.
+#[rustfmt::skip]
+#[bevy_trait_query::queryable]
+pub trait GoboDevice: DmxDevice {
+    /// Patterns on the gobo wheel, in order.
+    fn gobos(&self) -> &'static [GoboPattern];
+    /// Index into `gobos()` of the one in the beam.
+    fn gobo(&self) -> usize;
+    /// Gobo rotation in turns per second, negative for counter-clockwise.
+    fn gobo_rotation(&self) -> f32 { 0.0 }
+
+    /// Colors on the color wheel, in order.
+    fn colors(&self) -> &'static [Rgb] { &[Rgb::WHITE] }
+    /// Index into `colors()` of the one in the beam.
+    fn color_slot(&self) -> usize { 0 }
+
+    /// Number of prism facets, or `None` if the prism is out of the beam.
+    fn prism(&self) -> Option<u8> { None }
+    /// Prism rotation in turns per second, negative for counter-clockwise.
+    fn prism_rotation(&self) -> f32 { 0.0 }
+
+    /// The gobo in the beam.
+    fn pattern(&self) -> GoboPattern { self.gobos().get(self.gobo()).copied().unwrap_or(GoboPattern::Open) }
+    /// The color wheel color in the beam.
+    fn wheel_color(&self) -> Rgb { self.colors().get(self.color_slot()).copied().unwrap_or(Rgb::WHITE) }
+}
+
+/// A procedural approximation of a gobo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GoboPattern {
+    /// No gobo, the full beam.
+    Open,
+    /// A ring of `n` dots.
+    Dots(u8),
+    /// A star with `n` points.
+    Star(u8),
+    /// `n` spokes from the center.
+    Spokes(u8),
+    /// `n` parallel bars.
+    Bars(u8),
+    /// `n` concentric rings.
+    Rings(u8),
+    /// A spiral with `n` arms.
+    Spiral(u8),
+}
+
+impl GoboPattern {
+    /// Whether light passes through at `(x, y)`, from -1.0..1.0 across the gobo.
+    pub fn mask(self, x: f32, y: f32) -> bool {
+        let r = x.hypot(y);
+        let θ = y.atan2(x);
+        if r > 1.0 {
+            return false;
+        }
+
+        // Position around the gobo in units of 1/n turns, from 0.0..1.0 within each.
+        let sector = |n: u8| (θ / TAU * n.max(1) as f32).rem_euclid(1.0);
+
+        match self {
+            GoboPattern::Open => true,
+            GoboPattern::Dots(n) => {
+                let n = n.max(1) as f32;
+                let center = Vec2::from_angle((θ / TAU * n).round() / n * TAU) * 0.6;
+                let radius = (0.6 * (PI / n.max(2.0)).sin()).min(0.35) * 0.7;
+                Vec2::new(x, y).distance(center) < radius
+            }
+            GoboPattern::Star(n) => {
+                let point = (sector(n) - 0.5).abs() * 2.0;
+                r < 0.35 + 0.6 * point * point
+            }
+            GoboPattern::Spokes(n) => r > 0.1 && (sector(n) - 0.5).abs() < 0.25,
+            GoboPattern::Bars(n) => ((x + 1.0) / 2.0 * n.max(1) as f32).fract() < 0.5,
+            GoboPattern::Rings(n) => (r * n.max(1) as f32).fract() < 0.5,
+            GoboPattern::Spiral(n) => (sector(n) + r * 2.0).fract() < 0.5,
+        }
+    }
+
+    /// Render a `size`x`size` texture, white where light passes and transparent elsewhere.
+    pub fn image(self, size: u32) -> Image {
+        // 2x2 supersampling to smooth the edges.
+        let offsets = [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)];
+        let coord = |i: u32, offset: f32| (i as f32 + offset) / size as f32 * 2.0 - 1.0;
+
+        let mut data = Vec::with_capacity((size * size * 4) as usize);
+        for j in 0..size {
+            for i in 0..size {
+                let lit = offsets.iter().filter(|(dx, dy)| self.mask(coord(i, *dx), coord(j, *dy)));
+                let alpha = (lit.count() * 255 / offsets.len()) as u8;
+                data.extend([255, 255, 255, alpha]);
+            }
+        }
+
+        Image::new(
+            Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        )
+    }
+}
+
+/// Gobo textures, rendered the first time each pattern is used.
+#[derive(Resource, Default)]
+pub struct GoboImages(HashMap<GoboPattern, Handle<Image>>);
+
+impl GoboImages {
+    fn get(&mut self, pattern: GoboPattern, images: &mut Assets<Image>) -> Handle<Image> {
+        self.0
+            .entry(pattern)
+            .or_insert_with(|| images.add(pattern.image(GOBO_SIZE)))
+            .clone()
+    }
+}
+
+#[derive(Component)]
+pub struct GoboProjection {
+    light: Entity,
+    /// One quad per prism facet, hidden when unused.
+    facets: Vec<(Entity, Handle<StandardMaterial>)>,
+    /// Gobo and prism rotation, in turns.
+    gobo_angle: f32,
+    prism_angle: f32,
+}
+
+pub fn setup(
+    mut cmds: Commands,
+    fixtures: Query<(Entity, One<&dyn GoboDevice>), Without<GoboProjection>>,
+    children: Query<&Children>,
+    lights: Query<(), With<SpotLight>>,
+
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (entity, _device) in fixtures {
+        // Wait for the fixture's light to be spawned.
+        let Some(light) = children.iter_descendants(entity).find(|&child| lights.contains(child)) else {
+            continue;
+        };
+
+        let mesh = meshes.add(Plane3d::new(Vec3::Y, Vec2::splat(0.5)));
+        let facets: Vec<_> = (0..MAX_FACETS)
+            .map(|_| {
+                let material = materials.add(StandardMaterial {
+                    base_color: Color::BLACK,
+                    unlit: true,
+                    alpha_mode: AlphaMode::Add,
+                    ..Default::default()
+                });
+                let quad = cmds
+                    .spawn((
+                        Mesh3d(mesh.clone()),
+                        MeshMaterial3d(material.clone()),
+                        Transform::default(),
+                        Visibility::Hidden,
+                    ))
+                    .id();
+                (quad, material)
+            })
+            .collect();
+
+        // Children of the fixture so they go with it, though they're placed in world space.
+        cmds.entity(entity)
+            .add_children(&facets.iter().map(|&(quad, _)| quad).collect::<Vec<_>>())
+            .insert(GoboProjection { light, facets, gobo_angle: 0.0, prism_angle: 0.0 });
+    }
+}
+
+pub fn update(
+    mut fixtures: Query<(&mut GoboProjection, &GlobalTransform, One<&dyn GoboDevice>)>,
+    lights: Query<(&SpotLight, &GlobalTransform)>,
+    mut quads: Query<(&mut Transform, &mut Visibility)>,
+
+    mut gobos: ResMut<GoboImages>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+) {
+    for (mut projection, fixture, device) in &mut fixtures {
+        let dt = time.delta_secs();
+        projection.gobo_angle = (projection.gobo_angle + device.gobo_rotation() * dt).rem_euclid(1.0);
+        projection.prism_angle = (projection.prism_angle + device.prism_rotation() * dt).rem_euclid(1.0);
+
+        let Ok((light, transform)) = lights.get(projection.light) else {
+            continue;
+        };
+
+        // Project onto the floor like a light cookie would, ignoring anything in the way.
+        let (origin, dir) = (transform.translation(), transform.forward());
+        let hit = Ray3d::new(origin, dir)
+            .intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))
+            .filter(|&d| d <= light.range && light.intensity > 0.0);
+
+        let facets = device.prism().map_or(1, |n| (n as usize).clamp(1, MAX_FACETS));
+        for (i, (quad, _)) in projection.facets.iter().enumerate() {
+            let (_, mut visibility) = quads.get_mut(*quad).unwrap();
+            let visible = hit.is_some() && i < facets;
+            *visibility = if visible { Visibility::Visible } else { Visibility::Hidden };
+        }
+        let Some(d) = hit else {
+            continue;
+        };
+
+        // The beam lands as an ellipse, stretched along the beam the more obliquely it hits.
+        let radius = d * light.outer_angle.tan();
+        let incidence = dir.dot(Vec3::NEG_Y).max(0.05);
+        let along = Vec3::new(dir.x, 0.0, dir.z).try_normalize().unwrap_or(Vec3::Z);
+        let rotation = Quat::from_rotation_arc(Vec3::Z, along);
+        let scale = Vec3::new(radius, 1.0, radius / incidence) * 2.0;
+        let center = origin + dir * d + Vec3::Y * 0.001;
+
+        let texture = gobos.get(device.pattern(), &mut images);
+        let spin = Affine2::from_translation(Vec2::splat(0.5))
+            * Affine2::from_angle(projection.gobo_angle * TAU)
+            * Affine2::from_translation(Vec2::splat(-0.5));
+        // Facets split the light between them.
+        let color = light.color.to_linear().with_alpha(1.0 / facets as f32);
+
+        for (i, (quad, material)) in projection.facets.iter().take(facets).enumerate() {
+            // Each prism facet is a copy of the beam, fanned out around its center.
+            let offset = match facets {
+                1 => Vec2::ZERO,
+                n => Vec2::from_angle((projection.prism_angle + i as f32 / n as f32) * TAU) * 0.35,
+            };
+
+            let (mut transform, _) = quads.get_mut(*quad).unwrap();
+            let translation = center + rotation * (Vec3::new(offset.x, 0.0, offset.y) * scale);
+            let world = Transform { translation, rotation, scale };
+            *transform = GlobalTransform::from(world).reparented_to(fixture);
+
+            let material = materials.get_mut(material).unwrap();
+            material.base_color = color.into();
+            material.base_color_texture = Some(texture.clone());
+            material.uv_transform = spin;
+        }
+    }
+}
@@ -0,0 +1,375 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::render::mesh::PrimitiveTopology;
+
+use crate::dmx::device::laser_scan_30w::{Laser, LaserPattern, LaserStroke};
+use crate::laser::LaserFrame;
+use crate::prelude::*;
+
+/// How far the pattern is projected, in meters.
+const THROW: f32 = 8.0;
+/// Half the scan angle at full size, in degrees.
+const SCAN_ANGLE: f32 = 25.0;
+/// Brightness of the pattern, above 1.0 so it blooms.
+const GLOW: f32 = 4.0;
+/// Brightness of the beams fanning out to the pattern, relative to the pattern.
+const FAN: f32 = 0.08;
+
+/// A polyline in pattern space, from -1.0..1.0 on each axis with y up.
+type Stroke = Vec<Vec2>;
+
+#[derive(Component)]
+pub struct LaserSim {
+    mesh: Handle<Mesh>,
+    lines: Entity,
+}
+
+pub fn setup(
+    mut cmds: Commands,
+    lasers: Query<Entity, (Or<(With<Laser>, With<LaserFrame>)>, Without<LaserSim>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for entity in lasers {
+        let mesh = meshes.add(Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default()));
+        let material = materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            unlit: true,
+            alpha_mode: AlphaMode::Add,
+            ..Default::default()
+        });
+
+        let lines = cmds
+            .spawn((
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(material),
+                Transform::default(),
+                Visibility::Hidden,
+            ))
+            .id();
+        cmds.entity(entity).add_child(lines).insert(LaserSim { mesh, lines });
+    }
+}
+
+/// Redraw the pattern each frame, pointing down the fixture's -Z.
+pub fn update(
+    lasers: Query<(&Laser, &LaserSim)>,
+    mut visibility: Query<&mut Visibility>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (laser, sim) in lasers {
+        let mut vis = visibility.get_mut(sim.lines).unwrap();
+        if !laser.on {
+            *vis = Visibility::Hidden;
+            continue;
+        }
+        *vis = Visibility::Inherited;
+
+        let mut lines = Lines::default();
+        for stroke in shape(laser.pattern) {
+            for (a, b) in segments(&stroke, laser.stroke) {
+                lines.beam(transform(laser, a), transform(laser, b), laser.color.rgb());
+            }
+        }
+        lines.upload(meshes.get_mut(&sim.mesh).unwrap());
+    }
+}
+
+/// Redraw point streams when they change, same as `update()`.
+pub fn update_frames(
+    frames: Query<(&LaserFrame, &LaserSim), Or<(Changed<LaserFrame>, Added<LaserSim>)>>,
+    mut visibility: Query<&mut Visibility>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (frame, sim) in frames {
+        *visibility.get_mut(sim.lines).unwrap() = Visibility::Inherited;
+
+        let mut lines = Lines::default();
+        for (a, b) in frame.segments() {
+            lines.beam(a.pos, b.pos, b.color);
+        }
+        lines.upload(meshes.get_mut(&sim.mesh).unwrap());
+    }
+}
+
+/// Line geometry for a `LaserSim` mesh.
+#[derive(Default)]
+struct Lines {
+    positions: Vec<[f32; 3]>,
+    colors: Vec<[f32; 4]>,
+}
+
+impl Lines {
+    /// Draw a segment in pattern space, along with a faint fan of beams out to it.
+    fn beam(&mut self, from: Vec2, to: Vec2, color: Rgb) {
+        let project = |p: Vec2| (p * SCAN_ANGLE.to_radians().tan()).extend(-1.0) * THROW;
+        let (from, to) = (project(from), project(to));
+
+        let Rgb(r, g, b) = color * GLOW;
+        self.push(from, to, [r, g, b, 1.0]);
+        self.push(Vec3::ZERO, from, [r, g, b, FAN]);
+    }
+
+    fn push(&mut self, a: Vec3, b: Vec3, color: [f32; 4]) {
+        self.positions.extend([a.to_array(), b.to_array()]);
+        self.colors.extend([color, color]);
+    }
+
+    fn upload(self, mesh: &mut Mesh) {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.colors);
+    }
+}
+
+/// Move a point around in pattern space, by the pattern's position, size, rotation and flips.
+fn transform(laser: &Laser, p: Vec2) -> Vec2 {
+    // The flips turn the pattern around each axis, which foreshortens it.
+    let p = p * Vec2::new((laser.xflip * TAU).cos(), (laser.yflip * TAU).cos());
+    let p = Vec2::from_angle(laser.rotate * TAU).rotate(p);
+    let size = laser.size.inv().lerp(0.2..1.0);
+    p * size + Vec2::new(laser.x, laser.y)
+}
+
+/// Split strokes into line segments, leaving gaps for dotted strokes.
+fn segments(stroke: &[Vec2], style: LaserStroke) -> Vec<(Vec2, Vec2)> {
+    let segments = stroke.windows(2).map(|w| (w[0], w[1]));
+    match style {
+        LaserStroke::Solid(_) => segments.collect(),
+        LaserStroke::Dots(fr) => {
+            // Chop each segment into dashes, more of them the higher the rate.
+            let dashes = fr.lerp(4.0..24.0) as usize;
+            segments
+                .flat_map(|(a, b)| {
+                    (0..dashes).step_by(2).map(move |i| {
+                        let t = |i: usize| i as f32 / dashes as f32;
+                        (a.lerp(b, t(i)), a.lerp(b, t(i + 1)))
+                    })
+                })
+                .collect()
+        }
+    }
+}
+
+/// Approximate outline of a pattern, from what it looks like on the wall.
+fn shape(pattern: LaserPattern) -> Vec<Stroke> {
+    let pattern = match pattern {
+        LaserPattern::Raw(byte) => LaserPattern::from_byte(byte),
+        pattern => pattern,
+    };
+
+    match pattern {
+        LaserPattern::Square => vec![rect(0.7, 0.7)],
+        LaserPattern::SquareWide => vec![rect(1.0, 0.5)],
+        LaserPattern::SquareXWide => vec![rect(1.0, 0.25)],
+        LaserPattern::SquareBlock => vec![rect(0.7, 0.7), rect(0.35, 0.35)],
+        LaserPattern::Circle => vec![ellipse(0.7, 0.7)],
+        LaserPattern::CircleWide => vec![ellipse(1.0, 0.5)],
+        LaserPattern::CircleDash => {
+            (0..12).map(|i| arc(Vec2::ZERO, 0.7, i as f32 / 12.0, 0.5 / 12.0)).collect()
+        }
+        LaserPattern::CircleQuad => [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)]
+            .into_iter()
+            .map(|(x, y)| circle(Vec2::new(x, y) * 0.4, 0.3))
+            .collect(),
+        LaserPattern::CircleCircle => vec![ellipse(0.8, 0.8), ellipse(0.4, 0.4)],
+        LaserPattern::CircleSquare => vec![ellipse(0.8, 0.8), rect(0.55, 0.55)],
+        LaserPattern::CircleX => vec![ellipse(0.7, 0.7), line(-1.0, 0.0, 1.0, 0.0)],
+        LaserPattern::CircleY => vec![ellipse(0.7, 0.7), line(0.0, -1.0, 0.0, 1.0)],
+        LaserPattern::LineX => vec![line(-1.0, 0.0, 1.0, 0.0)],
+        LaserPattern::LineY => vec![line(0.0, -1.0, 0.0, 1.0)],
+        LaserPattern::LineXY => vec![line(-1.0, 0.0, 1.0, 0.0), line(0.0, -1.0, 0.0, 1.0)],
+        LaserPattern::LineDX => vec![line(-0.8, -0.8, 0.8, 0.8)],
+        LaserPattern::LineDY => vec![line(-0.8, 0.8, 0.8, -0.8)],
+        LaserPattern::Line2X => vec![line(-1.0, 0.3, 1.0, 0.3), line(-1.0, -0.3, 1.0, -0.3)],
+        LaserPattern::Line2Y => vec![line(0.3, -1.0, 0.3, 1.0), line(-0.3, -1.0, -0.3, 1.0)],
+        LaserPattern::LinePenta => vec![star_polygon(5, 2, 0.8)],
+        LaserPattern::LineStair => vec![
+            (0..=8usize)
+                .map(|i| Vec2::new(i.div_ceil(2) as f32, (i / 2) as f32) / 2.0 - 1.0)
+                .collect(),
+        ],
+        LaserPattern::Tri => vec![polygon(3, 0.8)],
+        LaserPattern::TriX => vec![polygon(3, 0.8), line(-1.0, -0.4, 1.0, -0.4)],
+        LaserPattern::TriY => vec![scaled(polygon(3, 0.8), Vec2::new(1.0, -1.0))],
+        LaserPattern::Tri3d => {
+            let tri = polygon(3, 0.8);
+            let spokes = tri[..3].iter().map(|&p| vec![Vec2::ZERO, p]);
+            spokes.chain([tri]).collect()
+        }
+        LaserPattern::TriTri => vec![polygon(3, 0.8), scaled(polygon(3, 0.8), Vec2::new(1.0, -1.0))],
+        LaserPattern::TriCircle => vec![polygon(3, 0.8), ellipse(0.8, 0.8)],
+        LaserPattern::TriWing => vec![
+            vec![
+                Vec2::ZERO,
+                Vec2::new(-1.0, 0.5),
+                Vec2::new(-1.0, -0.5),
+                Vec2::ZERO,
+            ],
+            vec![Vec2::ZERO, Vec2::new(1.0, 0.5), Vec2::new(1.0, -0.5), Vec2::ZERO],
+        ],
+        LaserPattern::TriArch => vec![
+            vec![Vec2::new(-0.8, -0.4), Vec2::new(0.0, 0.8), Vec2::new(0.8, -0.4)],
+            arc(Vec2::new(0.0, -0.4), 0.8, 0.5, 0.5),
+        ],
+        LaserPattern::Penta => vec![polygon(5, 0.8)],
+        LaserPattern::Squig1 => vec![
+            (0..=8)
+                .map(|i| Vec2::new(i as f32 / 4.0 - 1.0, if i % 2 == 0 { -0.3 } else { 0.3 }))
+                .collect(),
+        ],
+        LaserPattern::Squig2 => vec![wave(0.3, 0.3, 3.0), wave(-0.3, 0.3, 3.0)],
+        LaserPattern::Three => vec![pts(&[
+            (-0.4, 0.8),
+            (0.4, 0.8),
+            (0.0, 0.0),
+            (0.4, -0.4),
+            (0.0, -0.8),
+            (-0.4, -0.6),
+        ])],
+        LaserPattern::Two => vec![pts(&[
+            (-0.4, 0.5),
+            (0.0, 0.8),
+            (0.4, 0.5),
+            (-0.4, -0.8),
+            (0.4, -0.8),
+        ])],
+        LaserPattern::One => vec![
+            pts(&[(-0.2, 0.6), (0.0, 0.8), (0.0, -0.8)]),
+            line(-0.3, -0.8, 0.3, -0.8),
+        ],
+        LaserPattern::Music => vec![
+            circle(Vec2::new(-0.3, -0.6), 0.2),
+            pts(&[(-0.1, -0.6), (-0.1, 0.8), (0.5, 0.5)]),
+        ],
+        LaserPattern::Tree => vec![
+            pts(&[
+                (0.0, 0.9),
+                (-0.5, 0.3),
+                (-0.2, 0.3),
+                (-0.7, -0.4),
+                (0.7, -0.4),
+                (0.2, 0.3),
+                (0.5, 0.3),
+                (0.0, 0.9),
+            ]),
+            pts(&[(-0.1, -0.4), (-0.1, -0.8), (0.1, -0.8), (0.1, -0.4)]),
+        ],
+        LaserPattern::Star => vec![star_polygon(5, 2, 0.8)].into_iter().chain([polygon(5, 0.32)]).collect(),
+        LaserPattern::Sin => vec![wave(0.0, 0.5, 2.0)],
+        LaserPattern::Heart => vec![
+            (0..=64)
+                .map(|i| {
+                    let t = i as f32 / 64.0 * TAU;
+                    let x = 16.0 * t.sin().powi(3);
+                    let y = 13.0 * t.cos() - 5.0 * (2.0 * t).cos() - 2.0 * (3.0 * t).cos() - (4.0 * t).cos();
+                    Vec2::new(x, y) / 20.0
+                })
+                .collect(),
+        ],
+        LaserPattern::Elephant => vec![
+            ellipse(0.6, 0.4),
+            pts(&[(-0.6, 0.1), (-0.9, -0.2), (-0.8, -0.7)]),
+            line(-0.3, -0.35, -0.3, -0.8),
+            line(0.3, -0.35, 0.3, -0.8),
+        ],
+        LaserPattern::Apple => vec![circle(Vec2::new(0.0, -0.1), 0.6), pts(&[(0.0, 0.5), (0.1, 0.8)])],
+        LaserPattern::Plus => vec![line(-0.8, 0.0, 0.8, 0.0), line(0.0, -0.8, 0.0, 0.8)],
+        LaserPattern::PlusOval => vec![
+            line(-1.0, 0.0, 1.0, 0.0),
+            line(0.0, -0.6, 0.0, 0.6),
+            ellipse(0.8, 0.5),
+        ],
+        LaserPattern::PlusArrow => [0.0, 0.25, 0.5, 0.75]
+            .into_iter()
+            .flat_map(|turn| {
+                let r = Vec2::from_angle(turn * TAU);
+                let arrow = [
+                    pts(&[(0.0, 0.0), (0.8, 0.0)]),
+                    pts(&[(0.6, 0.2), (0.8, 0.0), (0.6, -0.2)]),
+                ];
+                arrow.map(|stroke| stroke.into_iter().map(|p| r.rotate(p)).collect::<Stroke>())
+            })
+            .collect(),
+        LaserPattern::PlusDia => vec![
+            line(-0.8, 0.0, 0.8, 0.0),
+            line(0.0, -0.8, 0.0, 0.8),
+            polygon(4, 0.8),
+        ],
+        LaserPattern::Arrow => vec![
+            pts(&[(-0.8, 0.0), (0.8, 0.0)]),
+            pts(&[(0.3, 0.5), (0.8, 0.0), (0.3, -0.5)]),
+        ],
+        LaserPattern::ArrowInvert => vec![
+            pts(&[(0.8, 0.0), (-0.8, 0.0)]),
+            pts(&[(-0.3, 0.5), (-0.8, 0.0), (-0.3, -0.5)]),
+        ],
+        LaserPattern::Hourglass1 => vec![pts(&[
+            (-0.6, 0.8),
+            (0.6, 0.8),
+            (-0.6, -0.8),
+            (0.6, -0.8),
+            (-0.6, 0.8),
+        ])],
+        LaserPattern::Hourglass2 => vec![
+            pts(&[(-0.6, 0.8), (0.6, -0.8)]),
+            pts(&[(0.6, 0.8), (-0.6, -0.8)]),
+            line(-0.6, 0.8, 0.6, 0.8),
+            line(-0.6, -0.8, 0.6, -0.8),
+        ],
+        // Anything we don't know the look of.
+        LaserPattern::Raw(_) => vec![ellipse(0.7, 0.7)],
+    }
+}
+
+fn pts(points: &[(f32, f32)]) -> Stroke {
+    points.iter().map(|&(x, y)| Vec2::new(x, y)).collect()
+}
+
+fn line(x0: f32, y0: f32, x1: f32, y1: f32) -> Stroke {
+    vec![Vec2::new(x0, y0), Vec2::new(x1, y1)]
+}
+
+fn rect(w: f32, h: f32) -> Stroke {
+    pts(&[(-w, -h), (w, -h), (w, h), (-w, h), (-w, -h)])
+}
+
+/// Arc around `center` starting at `start` turns and spanning `len` turns.
+fn arc(center: Vec2, r: f32, start: f32, len: f32) -> Stroke {
+    let n = (len * 64.0).ceil().max(2.0) as usize;
+    (0..=n)
+        .map(|i| center + Vec2::from_angle((start + len * i as f32 / n as f32) * TAU) * r)
+        .collect()
+}
+
+fn circle(center: Vec2, r: f32) -> Stroke {
+    arc(center, r, 0.0, 1.0)
+}
+
+fn ellipse(w: f32, h: f32) -> Stroke {
+    scaled(circle(Vec2::ZERO, 1.0), Vec2::new(w, h))
+}
+
+/// Regular polygon with a point at the top.
+fn polygon(sides: usize, r: f32) -> Stroke {
+    star_polygon(sides, 1, r)
+}
+
+/// Polygon through every `step`th of `points` points on a circle, with a point at the top.
+fn star_polygon(points: usize, step: usize, r: f32) -> Stroke {
+    (0..=points)
+        .map(|i| Vec2::from_angle(TAU / 4.0 + (i * step % points) as f32 / points as f32 * TAU) * r)
+        .collect()
+}
+
+/// Sine wave across the pattern at height `y`.
+fn wave(y: f32, amplitude: f32, cycles: f32) -> Stroke {
+    (0..=64)
+        .map(|i| {
+            let x = i as f32 / 32.0 - 1.0;
+            Vec2::new(x, y + amplitude * (x * cycles * PI).sin())
+        })
+        .collect()
+}
+
+fn scaled(stroke: Stroke, scale: Vec2) -> Stroke {
+    stroke.into_iter().map(|p| p * scale).collect()
+}
@@ -69,7 +69,7 @@ impl Personal {
 }
 
 impl DmxUniverse for Personal {
-    fn send(&self, e131: &mut E131) {
+    fn send(&self, out: &mut dyn DmxOutput) {
         let mut dmx = [0u8; 205];
 
         for (i, par) in self.pars.iter().enumerate() {
@@ -92,6 +92,7 @@ impl DmxUniverse for Personal {
         self.strobe.encode(&mut dmx[142..]);
         self.laser.encode(&mut dmx[164..]);
 
-        e131.send(&dmx);
+        out.send_universe(1, &dmx);
+        out.flush();
     }
 }
@@ -13,6 +13,11 @@ pub use moving_head::MovingHeadDevice;
 mod spot;
 pub use spot::SpotDevice;
 
+mod gobo;
+pub use gobo::{GoboDevice, GoboPattern};
+
+mod laser;
+
 pub struct LightsPlugin {
     pub models: Dir,
 }
@@ -21,12 +26,14 @@ impl Plugin for LightsPlugin {
     fn build(&self, app: &mut App) {
         // XXX: rethink this mess
         app.register_component_as::<dyn MovingHeadDevice, crate::lights::fixture::StealthBeam>();
+        app.register_component_as::<dyn DmxDevice, crate::lights::fixture::StealthBeam>();
         self.models.insert_asset(
             Path::new(crate::lights::fixture::StealthBeam::default().model_path()),
             crate::lights::fixture::StealthBeam::default().model(),
         );
 
         app.register_component_as::<dyn SpotDevice, crate::lights::fixture::SaberSpot>();
+        app.register_component_as::<dyn DmxDevice, crate::lights::fixture::SaberSpot>();
         self.models.insert_asset(
             Path::new(crate::lights::fixture::SaberSpot::default().model_path()),
             crate::lights::fixture::SaberSpot::default().model(),
@@ -34,9 +41,27 @@ impl Plugin for LightsPlugin {
 
         app.add_systems(
             PreUpdate,
-            (moving_head::setup_pre, moving_head::setup_post, moving_head::update),
+            (moving_head::setup_pre, moving_head::setup_post, moving_head::update).after(DmxDecode),
+        );
+
+        app.add_systems(PreUpdate, (spot::setup_pre, spot::setup_post, spot::update).after(DmxDecode));
+
+        // DmxDevice is already registered by DmxPlugin.
+        app.register_component_as::<dyn MovingHeadDevice, crate::dmx::device::gobo_60w::Gobo>();
+        app.register_component_as::<dyn GoboDevice, crate::dmx::device::gobo_60w::Gobo>();
+        self.models.insert_asset(
+            Path::new(crate::dmx::device::gobo_60w::Gobo::default().model_path()),
+            crate::dmx::device::gobo_60w::Gobo::default().model(),
+        );
+
+        app.init_resource::<gobo::GoboImages>();
+        app.add_systems(
+            PreUpdate,
+            (gobo::setup, gobo::update).after(moving_head::update).after(spot::update),
         );
 
-        app.add_systems(PreUpdate, (spot::setup_pre, spot::setup_post, spot::update));
+        app.add_systems(PreUpdate, (laser::setup, laser::update).after(DmxDecode));
+        // After anything in `Update` which draws frames.
+        app.add_systems(PostUpdate, laser::update_frames);
     }
 }
@@ -1,5 +1,6 @@
 use bevy::gltf::GltfMaterialName;
 
+use crate::dmx::NOMINAL_STROBE_HZ;
 use crate::prelude::*;
 use crate::sim::motor::{Motor, MotorDynamics};
 
@@ -20,10 +21,17 @@ pub trait MovingHeadDevice: DmxDevice {
     fn pitch_dynamics(&self) -> MotorDynamics;
     fn yaw_axis(&self) -> Axis { Axis::Y }
     fn yaw_dynamics(&self) -> MotorDynamics;
+    /// Whether pitch and yaw are sent as 16-bit coarse+fine channels.
+    fn fine_channels(&self) -> bool { false }
 
     fn pitch(&self) -> f32;
     fn yaw(&self) -> f32;
+    /// Color coming out of the fixture, after its dimmer.
     fn color(&self) -> Rgbw;
+    /// Shutter state, simulated at `strobe_hz()`.
+    fn shutter(&self) -> Shutter { Shutter::Open }
+    /// Strobe frequency range in Hz, from slowest to fastest. Override with the manual's figures.
+    fn strobe_hz(&self) -> (f32, f32) { NOMINAL_STROBE_HZ }
 }
 
 #[derive(Component)]
@@ -138,12 +146,16 @@ pub fn update(
     mut motors: Query<&mut Motor>,
     mut lights: Query<&mut SpotLight>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
 ) {
     for (fixture, device) in fixtures {
-        motors.get_mut(fixture.head).unwrap().rotate(device.pitch());
-        motors.get_mut(fixture.yoke).unwrap().rotate(device.yaw());
+        // Move to what the real fixture would, at the resolution it's sent with.
+        let quantize = |v: f32| if device.fine_channels() { v.word().float() } else { v.byte().float() };
+        motors.get_mut(fixture.head).unwrap().rotate(quantize(device.pitch()));
+        motors.get_mut(fixture.yoke).unwrap().rotate(quantize(device.yaw()));
 
-        let color = Rgb::from(device.color());
+        let lit = device.shutter().is_lit(time.elapsed_secs(), device.strobe_hz());
+        let color = if lit { Rgb::from(device.color()) } else { Rgb::BLACK };
         let Rgb(r, g, b) = color;
 
         let mut light = lights.get_mut(fixture.light).unwrap();
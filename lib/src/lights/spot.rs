@@ -1,5 +1,6 @@
 use bevy::gltf::GltfMaterialName;
 
+use crate::dmx::NOMINAL_STROBE_HZ;
 use crate::prelude::*;
 
 #[rustfmt::skip]
@@ -15,7 +16,12 @@ pub trait SpotDevice: DmxDevice {
     fn model(&self) -> &'static [u8];
     fn model_path(&self) -> &'static str;
 
+    /// Color coming out of the fixture, after its dimmer.
     fn color(&self) -> Rgbw;
+    /// Shutter state, simulated at `strobe_hz()`.
+    fn shutter(&self) -> Shutter { Shutter::Open }
+    /// Strobe frequency range in Hz, from slowest to fastest. Override with the manual's figures.
+    fn strobe_hz(&self) -> (f32, f32) { NOMINAL_STROBE_HZ }
 }
 
 #[derive(Component)]
@@ -108,9 +114,11 @@ pub fn update(
     fixtures: Query<(&Spot, One<&dyn SpotDevice>)>,
     mut lights: Query<&mut SpotLight>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
 ) {
     for (fixture, device) in fixtures {
-        let color = Rgb::from(device.color());
+        let lit = device.shutter().is_lit(time.elapsed_secs(), device.strobe_hz());
+        let color = if lit { Rgb::from(device.color()) } else { Rgb::BLACK };
         let Rgb(r, g, b) = color;
 
         let mut light = lights.get_mut(fixture.light).unwrap();
@@ -1,7 +1,7 @@
 pub trait Byte: Sized {
-    /// Convert 0..255 to 0..1f
+    /// Convert 0..255 (or 0..65535) to 0..1f
     fn float(self) -> f32;
-    /// Convert 0..127 to 0..1f
+    /// Convert 0..127 (or 0..16383) to 0..1f
     fn midi_float(self) -> f32;
 }
 
@@ -14,3 +14,13 @@ impl Byte for u8 {
         (self as f32) / 127.0
     }
 }
+
+impl Byte for u16 {
+    fn float(self) -> f32 {
+        (self as f32) / 65535.0
+    }
+
+    fn midi_float(self) -> f32 {
+        (self as f32) / 16383.0
+    }
+}
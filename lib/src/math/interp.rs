@@ -65,6 +65,8 @@ pub trait Interp: Sized {
 
     /// Convert 0..1 to 0..255u8
     fn byte(self) -> u8;
+    /// Convert 0..1 to 0..65535u16, e.g. for 16-bit coarse+fine DMX channels
+    fn word(self) -> u16;
     /// Convert 0..1 to 0..127u8
     fn midi_byte(self) -> u8;
 }
@@ -153,6 +155,9 @@ impl Interp for f32 {
     fn byte(self) -> u8 {
         self.clamp(0.0, 1.0).lerp(0..255) as u8
     }
+    fn word(self) -> u16 {
+        self.clamp(0.0, 1.0).lerp(0..65535) as u16
+    }
     fn midi_byte(self) -> u8 {
         self.clamp(0.0, 1.0).lerp(0..127) as u8
     }
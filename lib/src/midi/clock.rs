@@ -0,0 +1,257 @@
+//! MIDI beat clock, for following or leading the tempo of other gear.
+
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::{MidiDevice, MidiInput, MidiPlugin};
+use crate::prelude::*;
+
+/// Clock pulses per quarter note.
+pub const PPQN: u32 = 24;
+/// Clock pulses per step of a song position, which are 16th notes.
+const PULSES_PER_STEP: u32 = PPQN / 4;
+/// Number of recent pulses to fit the tempo to, two beats' worth.
+const WINDOW: usize = 2 * PPQN as usize;
+/// Longest gap between pulses before we consider the clock to have stopped.
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Follows a `Midi<MidiClock>` into `ClockSync`.
+pub struct MidiClockPlugin;
+impl Plugin for MidiClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MidiPlugin::<MidiClock>::default())
+            .init_resource::<ClockSync>()
+            .add_systems(PreUpdate, sync.after(super::receive::<MidiClock>));
+    }
+}
+
+/// A port sending or receiving MIDI clock, e.g. a DJ mixer.
+#[derive(Debug, Default)]
+pub struct MidiClock;
+
+/// System real-time messages for clock and transport.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockMessage {
+    /// One of `PPQN` pulses per beat.
+    Tick,
+    /// Play from the top.
+    Start,
+    /// Play from the song position.
+    Continue,
+    Stop,
+    /// Move to a song position in 16th notes, while stopped.
+    SongPosition(u16),
+}
+
+impl MidiDevice for MidiClock {
+    type Input = ClockMessage;
+    type Output = ClockMessage;
+
+    fn process_input(&mut self, raw: &[u8]) -> Option<ClockMessage> {
+        Some(match *raw {
+            [0xF8, ..] => ClockMessage::Tick,
+            [0xFA, ..] => ClockMessage::Start,
+            [0xFB, ..] => ClockMessage::Continue,
+            [0xFC, ..] => ClockMessage::Stop,
+            [0xF2, lsb, msb, ..] => ClockMessage::SongPosition((lsb as u16) | ((msb as u16) << 7)),
+            _ => return None,
+        })
+    }
+
+    fn process_output(&mut self, output: ClockMessage) -> Vec<u8> {
+        match output {
+            ClockMessage::Tick => vec![0xF8],
+            ClockMessage::Start => vec![0xFA],
+            ClockMessage::Continue => vec![0xFB],
+            ClockMessage::Stop => vec![0xFC],
+            ClockMessage::SongPosition(steps) => {
+                vec![0xF2, (steps & 0x7F) as u8, ((steps >> 7) & 0x7F) as u8]
+            }
+        }
+    }
+}
+
+/// Tempo and song position following a MIDI clock.
+///
+/// Pulses arrive with a millisecond or so of jitter, so rather than timing
+/// between consecutive pulses, the tempo and phase come from a line fit through
+/// when the last couple beats' worth arrived.
+#[derive(Resource, Default)]
+pub struct ClockSync {
+    /// When recent pulses arrived, oldest first.
+    pulses: VecDeque<Instant>,
+    /// Song position of the next pulse, in pulses.
+    next: u32,
+    playing: bool,
+}
+
+impl ClockSync {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle a message which arrived at `time`.
+    pub fn handle(&mut self, message: ClockMessage, time: Instant) {
+        match message {
+            ClockMessage::Tick => {
+                if self
+                    .pulses
+                    .back()
+                    .is_some_and(|&last| time.saturating_duration_since(last) > TIMEOUT)
+                {
+                    self.pulses.clear();
+                }
+                if self.pulses.len() == WINDOW {
+                    self.pulses.pop_front();
+                }
+                self.pulses.push_back(time);
+
+                if self.playing {
+                    self.next += 1;
+                }
+            }
+            ClockMessage::Start => {
+                self.playing = true;
+                self.next = 0;
+            }
+            ClockMessage::Continue => self.playing = true,
+            ClockMessage::Stop => self.playing = false,
+            ClockMessage::SongPosition(steps) => self.next = steps as u32 * PULSES_PER_STEP,
+        }
+    }
+
+    /// Whether the clock's transport is running.
+    pub fn playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Tempo of the clock, or `None` if there's been no clock recently enough.
+    pub fn bpm(&self) -> Option<f32> {
+        let (_, _, period) = self.fit()?;
+        Some((60.0 / (period * PPQN as f64)) as f32)
+    }
+
+    /// Fractional beats since the start of the song at `now`, or `None` while stopped.
+    pub fn beat(&self, now: Instant) -> Option<f32> {
+        if !self.playing {
+            return None;
+        }
+        let (base, offset, period) = self.fit()?;
+
+        // Pulses since the latest one, measured from the fit so its jitter doesn't show.
+        // Stops a beat in if the clock goes away.
+        let latest = offset + period * (self.pulses.len() - 1) as f64;
+        let since = (now.saturating_duration_since(base).as_secs_f64() - latest) / period;
+        let pulse = self.next as f64 - 1.0 + since.clamp(-1.0, PPQN as f64);
+
+        Some((pulse.max(0.0) / PPQN as f64) as f32)
+    }
+
+    /// Least squares fit of pulse arrival times, as `(base, offset, period)` where
+    /// pulse `i` lands at `base + offset + i * period` seconds.
+    fn fit(&self) -> Option<(Instant, f64, f64)> {
+        let n = self.pulses.len();
+        if n < PULSES_PER_STEP as usize {
+            return None;
+        }
+
+        let base = self.pulses[0];
+        let mean_x = (n - 1) as f64 / 2.0;
+        let ys = self.pulses.iter().map(|t| t.duration_since(base).as_secs_f64());
+        let mean_y = ys.clone().sum::<f64>() / n as f64;
+
+        let (mut cov, mut var) = (0.0, 0.0);
+        for (x, y) in ys.enumerate() {
+            let dx = x as f64 - mean_x;
+            cov += dx * (y - mean_y);
+            var += dx * dx;
+        }
+
+        let period = cov / var;
+        (period > 0.0).then_some((base, mean_y - period * mean_x, period))
+    }
+}
+
+/// System to update `ClockSync` from the clock input.
+pub fn sync(mut clock: ResMut<ClockSync>, mut inputs: EventReader<MidiInput<MidiClock>>) {
+    for input in inputs.read() {
+        clock.handle(input.input, input.time);
+    }
+}
+
+/// Sends MIDI clock at a tempo.
+///
+/// Pulses are timed on their own thread rather than sent once a frame, since
+/// at 120 bpm they're only 21 ms apart.
+#[derive(Resource)]
+pub struct ClockOut {
+    bpm: f32,
+    tx: mpsc::Sender<Command>,
+    _thread: JoinHandle<()>,
+}
+
+enum Command {
+    Bpm(f32),
+    Send(ClockMessage),
+}
+
+impl ClockOut {
+    /// Send pulses at `bpm` to `out`, e.g. a `Midi<MidiClock>` moved into the closure.
+    ///
+    /// Pulses begin with the first `start()`, so they always follow a Start.
+    pub fn new(bpm: f32, mut out: impl FnMut(ClockMessage) + Send + 'static) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let _thread = thread::spawn(move || {
+            let pulse = |bpm: f32| Duration::from_secs_f32(60.0 / (bpm.max(1.0) * PPQN as f32));
+
+            let mut bpm = bpm;
+            // When to send the next pulse, once started.
+            let mut next = None;
+            loop {
+                let command = match next {
+                    Some(next) => rx.recv_timeout(next.saturating_duration_since(Instant::now())),
+                    None => rx.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+                };
+                match command {
+                    Ok(Command::Bpm(b)) => bpm = b,
+                    Ok(Command::Send(message)) => {
+                        out(message);
+                        // Pulses count from a start.
+                        if message == ClockMessage::Start {
+                            next = Some(Instant::now());
+                        }
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        out(ClockMessage::Tick);
+                        // Skip pulses rather than bursting them if we fall behind.
+                        next = next.map(|next| (next + pulse(bpm)).max(Instant::now()));
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        });
+
+        Self { bpm, tx, _thread }
+    }
+
+    pub fn bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    pub fn set_bpm(&mut self, bpm: f32) {
+        if bpm != self.bpm {
+            self.bpm = bpm;
+            let _ = self.tx.send(Command::Bpm(bpm));
+        }
+    }
+
+    pub fn start(&self) {
+        let _ = self.tx.send(Command::Send(ClockMessage::Start));
+    }
+
+    pub fn stop(&self) {
+        let _ = self.tx.send(Command::Send(ClockMessage::Stop));
+    }
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+use super::MidiDevice;
+use crate::math::{Byte, Interp};
+
+/// Any MIDI controller, as plain channel messages with values normalized to 0.0..1.0.
+///
+/// For controllers without a device of their own, usually with `MidiLearn` to bind
+/// their controls to parameters. Outputs are the same messages, e.g. notes to light
+/// up pads.
+#[derive(Debug, Default)]
+pub struct GenericMidi;
+
+/// A channel message. Channels are 0-indexed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Message {
+    /// Note on, or off with a velocity of 0.0.
+    Note {
+        channel: u8,
+        note: u8,
+        velocity: f32,
+    },
+    Cc {
+        channel: u8,
+        cc: u8,
+        value: f32,
+    },
+    /// Ranges from -1.0..1.0, centered at 0.0.
+    PitchBend {
+        channel: u8,
+        value: f32,
+    },
+    /// Aftertouch on a single note.
+    PolyPressure {
+        channel: u8,
+        note: u8,
+        value: f32,
+    },
+    /// Aftertouch on the whole channel.
+    Pressure {
+        channel: u8,
+        value: f32,
+    },
+    Program {
+        channel: u8,
+        program: u8,
+    },
+}
+
+/// A single control on a controller, regardless of its value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Control {
+    Note { channel: u8, note: u8 },
+    Cc { channel: u8, cc: u8 },
+    PitchBend { channel: u8 },
+    PolyPressure { channel: u8, note: u8 },
+    Pressure { channel: u8 },
+    Program { channel: u8 },
+}
+
+impl Message {
+    /// The control this message came from.
+    pub fn control(self) -> Control {
+        match self {
+            Message::Note { channel, note, .. } => Control::Note { channel, note },
+            Message::Cc { channel, cc, .. } => Control::Cc { channel, cc },
+            Message::PitchBend { channel, .. } => Control::PitchBend { channel },
+            Message::PolyPressure { channel, note, .. } => Control::PolyPressure { channel, note },
+            Message::Pressure { channel, .. } => Control::Pressure { channel },
+            Message::Program { channel, .. } => Control::Program { channel },
+        }
+    }
+
+    /// The message's value, with programs from 0.0..1.0 like everything else.
+    pub fn value(self) -> f32 {
+        match self {
+            Message::Note { velocity: value, .. }
+            | Message::Cc { value, .. }
+            | Message::PitchBend { value, .. }
+            | Message::PolyPressure { value, .. }
+            | Message::Pressure { value, .. } => value,
+            Message::Program { program, .. } => program.midi_float(),
+        }
+    }
+}
+
+impl MidiDevice for GenericMidi {
+    type Input = Message;
+    type Output = Message;
+
+    fn process_input(&mut self, raw: &[u8]) -> Option<Message> {
+        let channel = raw.first()? & 0x0F;
+        let data = |i: usize| raw.get(i).map(|b| b & 0x7F);
+
+        Some(match raw[0] & 0xF0 {
+            0x80 => Message::Note { channel, note: data(1)?, velocity: 0.0 },
+            0x90 => Message::Note { channel, note: data(1)?, velocity: data(2)?.midi_float() },
+            0xA0 => Message::PolyPressure { channel, note: data(1)?, value: data(2)?.midi_float() },
+            0xB0 => Message::Cc { channel, cc: data(1)?, value: data(2)?.midi_float() },
+            0xC0 => Message::Program { channel, program: data(1)? },
+            0xD0 => Message::Pressure { channel, value: data(1)?.midi_float() },
+            0xE0 => {
+                let bend = (data(1)? as u16) | ((data(2)? as u16) << 7);
+                Message::PitchBend { channel, value: (bend as f32 - 8192.0) / 8192.0 }
+            }
+            // System messages aren't tied to a control.
+            _ => return None,
+        })
+    }
+
+    fn process_output(&mut self, output: Message) -> Vec<u8> {
+        let status = |kind: u8, channel: u8| kind | (channel & 0x0F);
+        match output {
+            Message::Note { channel, note, velocity } => {
+                vec![status(0x90, channel), note & 0x7F, velocity.midi_byte()]
+            }
+            Message::PolyPressure { channel, note, value } => {
+                vec![status(0xA0, channel), note & 0x7F, value.midi_byte()]
+            }
+            Message::Cc { channel, cc, value } => vec![status(0xB0, channel), cc & 0x7F, value.midi_byte()],
+            Message::Program { channel, program } => vec![status(0xC0, channel), program & 0x7F],
+            Message::Pressure { channel, value } => vec![status(0xD0, channel), value.midi_byte()],
+            Message::PitchBend { channel, value } => {
+                let bend = (value.clamp(-1.0, 1.0) * 8192.0 + 8192.0).round().min(16383.0) as u16;
+                vec![status(0xE0, channel), (bend & 0x7F) as u8, (bend >> 7) as u8]
+            }
+        }
+    }
+}
@@ -1,5 +1,4 @@
 use super::MidiDevice;
-use crate::midi::Midi;
 
 pub mod types;
 use types::*;
@@ -120,7 +119,7 @@ impl MidiDevice for LaunchControlXL {
     type Input = Input;
     type Output = Output;
 
-    fn init(ctrl: &mut Midi<Self>) {
+    fn init(&mut self) -> Vec<Output> {
         use types::*;
 
         let mut batch = vec![];
@@ -139,7 +138,7 @@ impl MidiDevice for LaunchControlXL {
         batch.push((Led::Mute, Color::Red, Brightness::Off));
         batch.push((Led::Solo, Color::Red, Brightness::Off));
         batch.push((Led::Record, Color::Red, Brightness::Off));
-        ctrl.send(Output::Batch(batch));
+        vec![Output::Batch(batch)]
     }
 
     fn process_input(&mut self, raw: &[u8]) -> Option<Input> {
@@ -1,17 +1,22 @@
 use std::fmt::Debug;
 
-use super::Midi;
-
 pub trait MidiDevice: Sized + Send + 'static {
-    type Input: Send + Debug;
+    type Input: Send + Sync + Debug;
     type Output: Send + Debug;
 
     fn process_input(&mut self, data: &[u8]) -> Option<Self::Input>;
     fn process_output(&mut self, output: Self::Output) -> Vec<u8>;
 
-    fn init(_midi: &mut Midi<Self>) {}
+    /// Outputs to send each time the device connects, to put it in a known state.
+    fn init(&mut self) -> Vec<Self::Output> {
+        vec![]
+    }
+
+    /// Forget anything cached about the device's state, since it may have been unplugged.
+    fn reset(&mut self) {}
 }
 
+pub mod generic;
 pub mod launch_control_xl;
 pub mod launchpad_x;
 pub mod worlde_easycontrol9;
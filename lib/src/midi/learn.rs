@@ -0,0 +1,150 @@
+//! MIDI learn, for binding controls on any controller to named parameters.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use super::device::generic::{Control, GenericMidi, Message};
+use super::{MidiInput, MidiPlugin};
+use crate::prelude::*;
+
+/// Turns inputs from a `Midi<GenericMidi>` into `MidiParam` events with a `MidiLearn`.
+pub struct MidiLearnPlugin;
+impl Plugin for MidiLearnPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MidiPlugin::<GenericMidi>::default())
+            .add_event::<MidiParam>()
+            .add_systems(PreUpdate, learn.after(super::receive::<GenericMidi>));
+    }
+}
+
+/// A bound parameter changed.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct MidiParam {
+    pub name: String,
+    pub value: f32,
+}
+
+/// Bindings from controls to named parameters, learned by moving the control.
+///
+/// Call `learn()` with a parameter, then the next control moved is bound to it.
+/// When loaded from a file, bindings are saved back to it as they're learned:
+///
+/// ```ron
+/// {
+///     "brightness": Cc(channel: 0, cc: 7),
+///     "strobe": Note(channel: 9, note: 36),
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct MidiLearn {
+    path: Option<PathBuf>,
+    bindings: BTreeMap<String, Control>,
+    /// Parameter waiting for a control to be moved.
+    learning: Option<String>,
+    /// Latest value of each parameter.
+    values: HashMap<String, f32>,
+}
+
+impl MidiLearn {
+    /// Bindings which aren't saved anywhere.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load bindings from a RON file, or start with none if it doesn't exist yet.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let bindings = match std::fs::read_to_string(path) {
+            Ok(ron) => {
+                ron::from_str(&ron).with_context(|| format!("Failed to parse MIDI bindings {path:?}"))?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {path:?}")),
+        };
+        Ok(Self { path: Some(path.to_path_buf()), bindings, ..Default::default() })
+    }
+
+    /// Save the bindings to the file they were loaded from, if any.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let ron = ron::ser::to_string_pretty(&self.bindings, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, ron).with_context(|| format!("Failed to write {path:?}"))
+    }
+
+    /// Bind the next control moved to `param`.
+    pub fn learn(&mut self, param: &str) {
+        self.learning = Some(param.to_string());
+    }
+
+    /// The parameter waiting for a control, if any.
+    pub fn learning(&self) -> Option<&str> {
+        self.learning.as_deref()
+    }
+
+    pub fn cancel(&mut self) {
+        self.learning = None;
+    }
+
+    pub fn bind(&mut self, param: &str, control: Control) {
+        // Each control drives a single parameter.
+        self.bindings.retain(|_, bound| *bound != control);
+        self.bindings.insert(param.to_string(), control);
+    }
+
+    pub fn unbind(&mut self, param: &str) {
+        self.bindings.remove(param);
+    }
+
+    /// The control bound to a parameter.
+    pub fn binding(&self, param: &str) -> Option<Control> {
+        self.bindings.get(param).copied()
+    }
+
+    /// Latest value of a parameter, if its control has moved.
+    pub fn value(&self, param: &str) -> Option<f32> {
+        self.values.get(param).copied()
+    }
+
+    /// Handle an input, returning the parameter it changed and its value.
+    ///
+    /// Learns a binding first if `learn()` was called. Note releases are ignored
+    /// while learning, so a pad is bound by pressing it.
+    pub fn handle(&mut self, message: Message) -> Option<(&str, f32)> {
+        let control = message.control();
+        let released = matches!(message, Message::Note { velocity, .. } if velocity == 0.0);
+        if !released && let Some(param) = self.learning.take() {
+            info!("Bound {param:?} to {control:?}");
+            self.bind(&param, control);
+        }
+
+        let (param, _) = self.bindings.iter().find(|(_, bound)| **bound == control)?;
+        let value = message.value();
+        self.values.insert(param.clone(), value);
+        Some((param.as_str(), value))
+    }
+}
+
+/// System to learn bindings and send `MidiParam`s.
+pub fn learn(
+    midi_learn: Option<ResMut<MidiLearn>>,
+    mut inputs: EventReader<MidiInput<GenericMidi>>,
+    mut params: EventWriter<MidiParam>,
+) -> Result {
+    let Some(mut midi_learn) = midi_learn else {
+        return Ok(());
+    };
+
+    for input in inputs.read() {
+        let learning = midi_learn.learning().is_some();
+        if let Some((name, value)) = midi_learn.handle(input.input) {
+            params.write(MidiParam { name: name.to_string(), value });
+        }
+        // Save as soon as something's learned.
+        if learning && midi_learn.learning().is_none() {
+            midi_learn.save()?;
+        }
+    }
+    Ok(())
+}
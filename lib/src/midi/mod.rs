@@ -1,98 +1,191 @@
-use std::sync::{Mutex, mpsc};
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
 use std::thread;
 use std::thread::JoinHandle;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
-use midir::{MidiInput, MidiInputConnection, MidiOutput};
+use anyhow::{Context, Result, anyhow};
+use midir::{MidiIO, MidiInputConnection, MidiOutput, MidiOutputConnection};
 
 use crate::prelude::*;
 
+pub mod clock;
 pub mod device;
+pub mod learn;
+pub use clock::{ClockOut, ClockSync, MidiClock, MidiClockPlugin};
 pub use device::MidiDevice;
+pub use device::generic::GenericMidi;
+pub use learn::{MidiLearn, MidiLearnPlugin, MidiParam};
 
-/// A MIDI device.
+/// How often to look for a device that isn't connected, or check that one still is.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Publishes `MidiInput<D>` and `MidiConnection` events for a `Midi<D>` resource,
+/// and keeps its entry in `MidiStatus` up to date.
+///
+/// Add one for each type of device, e.g. `MidiPlugin::<LaunchpadX>::default()`.
+pub struct MidiPlugin<D>(PhantomData<fn() -> D>);
+
+impl<D> Default for MidiPlugin<D> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<D: MidiDevice> Plugin for MidiPlugin<D> {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MidiInput<D>>()
+            .add_event::<MidiConnection>()
+            .init_resource::<MidiStatus>()
+            .add_systems(PreUpdate, receive::<D>);
+    }
+}
+
+/// An input from a MIDI device.
+#[derive(Event)]
+pub struct MidiInput<D: MidiDevice> {
+    pub input: D::Input,
+    /// When the message arrived.
+    pub time: Instant,
+}
+
+/// Sent when a MIDI device is plugged in or unplugged.
+#[derive(Event, Clone, Debug)]
+pub struct MidiConnection {
+    pub name: String,
+    pub connected: bool,
+}
+
+/// Whether each `Midi` device is connected, by name. Shown in the UI.
+#[derive(Resource, Default, Debug)]
+pub struct MidiStatus(BTreeMap<String, bool>);
+
+impl MidiStatus {
+    pub fn iter(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.0.iter().map(|(name, &connected)| (name.as_str(), connected))
+    }
+}
+
+/// A MIDI device, which is connected whenever it's plugged in.
+///
+/// Ports are matched by name, and rescanned every second while disconnected. On
+/// each (re)connect the device is `reset()` and its `init()` outputs are sent, then
+/// any outputs from `on_connect()`. Outputs sent while disconnected are dropped.
+///
+/// The device lives on a worker thread which sleeps until there's input or output,
+/// so messages are handled as soon as they arrive.
 #[derive(Resource)]
-pub enum Midi<D: MidiDevice> {
-    Connected {
-        in_rx: Mutex<mpsc::Receiver<D::Input>>,
-        out_tx: Mutex<mpsc::Sender<D::Output>>,
+pub struct Midi<D: MidiDevice> {
+    name: String,
+    in_rx: Mutex<mpsc::Receiver<(Instant, D::Input)>>,
+    wake_tx: Mutex<mpsc::Sender<Wake<D>>>,
+    connection_rx: Mutex<mpsc::Receiver<bool>>,
+    shared: Arc<Shared<D>>,
+
+    _thread: JoinHandle<()>,
+}
 
-        _raw: MidiRaw,
-        _thread: JoinHandle<()>,
-    },
-    Disconnected,
+type Hook<D> = Box<dyn Fn() -> Vec<<D as MidiDevice>::Output> + Send>;
+
+/// State shared with the worker thread.
+struct Shared<D: MidiDevice> {
+    connected: AtomicBool,
+    /// Outputs from `on_connect()`. Held by the worker while connecting, so
+    /// they're sent exactly once per connection.
+    on_connect: Mutex<Hook<D>>,
+}
+
+/// Work for the worker thread.
+enum Wake<D: MidiDevice> {
+    /// A raw message from the device, and when it arrived.
+    Input(Instant, Vec<u8>),
+    Output(D::Output),
+    /// The `Midi` was dropped.
+    Close,
 }
 
 impl<D: MidiDevice> Midi<D> {
-    /// Try to open a MIDI device.
-    pub fn new(name: &str, mut device: D) -> Self {
-        match MidiRaw::connect(name) {
-            Ok((_raw, raw_rx, raw_tx)) => {
-                let (in_tx, in_rx) = mpsc::channel::<D::Input>();
-                let (out_tx, out_rx) = mpsc::channel::<D::Output>();
-
-                let _name = name.to_string();
-                let _thread = thread::spawn(move || {
-                    loop {
-                        if let Ok(data) = raw_rx.try_recv() {
-                            if let Some(input) = device.process_input(&data) {
-                                trace!("{_name} <- {input:?}");
-                                in_tx.send(input).unwrap();
-                            }
-                        }
+    /// Open a MIDI device by a substring of its port name.
+    pub fn new(name: &str, device: D) -> Self {
+        Self::open(name, device, false)
+    }
 
-                        if let Ok(output) = out_rx.try_recv() {
-                            trace!("{_name} -> {output:?}");
-                            let data = device.process_output(output);
-                            if !data.is_empty() {
-                                raw_tx.send(data).unwrap();
-                            }
-                        }
+    /// Open only the output port of a MIDI device, e.g. a synth's MIDI in. There are never any inputs.
+    pub fn output(name: &str, device: D) -> Self {
+        Self::open(name, device, true)
+    }
 
-                        thread::sleep(Duration::from_millis(1));
-                    }
-                });
+    fn open(name: &str, device: D, output_only: bool) -> Self {
+        let (in_tx, in_rx) = mpsc::channel::<(Instant, D::Input)>();
+        let (wake_tx, wake_rx) = mpsc::channel::<Wake<D>>();
+        let (connection_tx, connection_rx) = mpsc::channel::<bool>();
+        let shared = Arc::new(Shared {
+            connected: AtomicBool::new(false),
+            on_connect: Mutex::new(Box::new(Vec::new) as Hook<D>),
+        });
 
-                let in_rx = Mutex::new(in_rx);
-                let out_tx = Mutex::new(out_tx);
+        let worker = Worker {
+            name: name.to_string(),
+            output_only,
+            device,
+            in_tx,
+            wake_tx: wake_tx.clone(),
+            wake_rx,
+            connection_tx,
+            shared: Arc::clone(&shared),
+        };
+        let _thread = thread::spawn(move || worker.run());
 
-                let mut this = Self::Connected { in_rx, out_tx, _raw, _thread };
-                D::init(&mut this);
-                this
-            }
-            Err(e) => {
-                warn!("Failed to open MIDI {name:?}: {e}");
-                Self::Disconnected
-            }
+        Self {
+            name: name.to_string(),
+            in_rx: Mutex::new(in_rx),
+            wake_tx: Mutex::new(wake_tx),
+            connection_rx: Mutex::new(connection_rx),
+            shared,
+            _thread,
         }
     }
 
-    /// Call the given callback with any pending MIDI events.
-    pub fn recv(&mut self) -> Vec<D::Input> {
-        match self {
-            Midi::Connected { in_rx, .. } => {
-                let mut msgs = vec![];
-                while let Ok(event) = in_rx.lock().unwrap().try_recv() {
-                    msgs.push(event);
-                }
-                msgs
+    /// Also send these outputs each time the device connects, after its own `init()`.
+    pub fn on_connect(self, outputs: Vec<D::Output>) -> Self
+    where
+        D::Output: Clone,
+    {
+        let mut on_connect = self.shared.on_connect.lock().unwrap();
+        // Already connected without them, so send them now.
+        if self.connected() {
+            let wake_tx = self.wake_tx.lock().unwrap();
+            for output in outputs.iter().cloned() {
+                let _ = wake_tx.send(Wake::Output(output));
             }
-            Midi::Disconnected => vec![],
         }
+        *on_connect = Box::new(move || outputs.clone());
+        drop(on_connect);
+        self
+    }
+
+    /// The name the device was opened with.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the device is currently plugged in.
+    pub fn connected(&self) -> bool {
+        self.shared.connected.load(Ordering::Acquire)
     }
 
     /// Send a MIDI event.
     pub fn send(&mut self, output: D::Output) {
-        match self {
-            Midi::Connected { out_tx, .. } => out_tx.lock().unwrap().send(output).unwrap(),
-            Midi::Disconnected => {}
+        if self.connected() {
+            let _ = self.wake_tx.lock().unwrap().send(Wake::Output(output));
         }
     }
 
     /// Log all available midi devices.
     pub fn list() -> Result<()> {
-        let midi_in = MidiInput::new(&format!("_list_inputs"))?;
+        let midi_in = midir::MidiInput::new(&format!("_list_inputs"))?;
         let midi_out = MidiOutput::new(&format!("_list_outputs"))?;
 
         for port in midi_in.ports() {
@@ -107,55 +200,215 @@ impl<D: MidiDevice> Midi<D> {
     }
 }
 
+impl<D: MidiDevice> Drop for Midi<D> {
+    fn drop(&mut self) {
+        let _ = self.wake_tx.lock().unwrap().send(Wake::Close);
+    }
+}
+
+/// System to publish `MidiInput<D>` and `MidiConnection` events.
+pub fn receive<D: MidiDevice>(
+    midi: Option<Res<Midi<D>>>,
+    mut status: ResMut<MidiStatus>,
+    mut inputs: EventWriter<MidiInput<D>>,
+    mut connections: EventWriter<MidiConnection>,
+) {
+    let Some(midi) = midi else {
+        return;
+    };
+    if !status.0.contains_key(&midi.name) {
+        status.0.insert(midi.name.clone(), midi.connected());
+    }
+    for connected in midi.connection_rx.lock().unwrap().try_iter() {
+        status.0.insert(midi.name.clone(), connected);
+        connections.write(MidiConnection { name: midi.name.clone(), connected });
+    }
+    for (time, input) in midi.in_rx.lock().unwrap().try_iter() {
+        inputs.write(MidiInput { input, time });
+    }
+}
+
+/// Owns the device on its own thread, connecting and reconnecting to it.
+struct Worker<D: MidiDevice> {
+    name: String,
+    /// Whether to connect without an input port.
+    output_only: bool,
+    device: D,
+    in_tx: mpsc::Sender<(Instant, D::Input)>,
+    /// Handed to the input callback of each connection.
+    wake_tx: mpsc::Sender<Wake<D>>,
+    wake_rx: mpsc::Receiver<Wake<D>>,
+    connection_tx: mpsc::Sender<bool>,
+    shared: Arc<Shared<D>>,
+}
+
+impl<D: MidiDevice> Worker<D> {
+    /// Run until the `Midi` is dropped.
+    fn run(mut self) {
+        let mut warned = false;
+        loop {
+            let wake_tx = self.wake_tx.clone();
+            let on_input = move |time, data: &[u8]| {
+                let _ = wake_tx.send(Wake::Input(time, data.to_vec()));
+            };
+
+            let raw = match self.output_only {
+                true => MidiRaw::connect_output(&self.name),
+                false => MidiRaw::connect(&self.name, on_input),
+            };
+            match raw {
+                Ok(mut raw) => {
+                    info!("Connected to MIDI {:?}", self.name);
+                    warned = false;
+
+                    let open = self.connect(&mut raw) && self.serve(&mut raw);
+                    drop(raw);
+
+                    self.shared.connected.store(false, Ordering::Release);
+                    self.device.reset();
+                    if !open || self.connection_tx.send(false).is_err() {
+                        return;
+                    }
+                    warn!("Lost MIDI {:?}, reconnecting", self.name);
+                }
+                Err(e) if !warned => {
+                    warn!("Failed to open MIDI {:?}, retrying: {e}", self.name);
+                    warned = true;
+                }
+                Err(_) => {}
+            }
+
+            if !self.wait() {
+                return;
+            }
+        }
+    }
+
+    /// Bring the device to a known state, returning false if the `Midi` was dropped.
+    fn connect(&mut self, raw: &mut MidiRaw) -> bool {
+        self.device.reset();
+
+        let shared = Arc::clone(&self.shared);
+        let on_connect = shared.on_connect.lock().unwrap();
+        for output in self.device.init().into_iter().chain((*on_connect)()) {
+            self.send(raw, output);
+        }
+        shared.connected.store(true, Ordering::Release);
+        drop(on_connect);
+
+        self.connection_tx.send(true).is_ok()
+    }
+
+    /// Handle messages as they come until the device goes away, returning false if the `Midi` was dropped.
+    fn serve(&mut self, raw: &mut MidiRaw) -> bool {
+        let mut rescan = Instant::now() + RESCAN_INTERVAL;
+        loop {
+            match self.wake_rx.recv_timeout(rescan.saturating_duration_since(Instant::now())) {
+                Ok(Wake::Input(time, data)) => {
+                    if let Some(input) = self.device.process_input(&data) {
+                        trace!("{} <- {input:?}", self.name);
+                        if self.in_tx.send((time, input)).is_err() {
+                            return false;
+                        }
+                    }
+                }
+                Ok(Wake::Output(output)) => {
+                    if !self.send(raw, output) {
+                        return true;
+                    }
+                }
+                Ok(Wake::Close) | Err(mpsc::RecvTimeoutError::Disconnected) => return false,
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+            }
+
+            // Checked here rather than on timeout, since a busy device might never time out.
+            if Instant::now() >= rescan {
+                if !raw.present() {
+                    return true;
+                }
+                rescan = Instant::now() + RESCAN_INTERVAL;
+            }
+        }
+    }
+
+    /// Send an output, returning false if the device went away.
+    fn send(&mut self, raw: &mut MidiRaw, output: D::Output) -> bool {
+        trace!("{} -> {output:?}", self.name);
+        let data = self.device.process_output(output);
+        if data.is_empty() {
+            return true;
+        }
+        match raw.send(&data) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Failed to send to MIDI {:?}: {e}", self.name);
+                false
+            }
+        }
+    }
+
+    /// Wait to rescan, returning false if the `Midi` was dropped.
+    ///
+    /// Outputs sent in the meantime are dropped, since they'd be stale by the time we reconnect.
+    fn wait(&mut self) -> bool {
+        let rescan = Instant::now() + RESCAN_INTERVAL;
+        loop {
+            match self.wake_rx.recv_timeout(rescan.saturating_duration_since(Instant::now())) {
+                Ok(Wake::Close) | Err(mpsc::RecvTimeoutError::Disconnected) => return false,
+                Ok(_) => {}
+                Err(mpsc::RecvTimeoutError::Timeout) => return true,
+            }
+        }
+    }
+}
+
 pub struct MidiRaw {
-    _in_conn: MidiInputConnection<()>,
-    _out_thread: JoinHandle<()>,
+    name: String,
+    /// Client used to check that the ports are still around.
+    scan: MidiOutput,
+    out_conn: MidiOutputConnection,
+    _in_conn: Option<MidiInputConnection<()>>,
 }
 
-#[allow(clippy::type_complexity)]
 impl MidiRaw {
-    pub fn connect(name: &str) -> Result<(Self, mpsc::Receiver<Vec<u8>>, mpsc::Sender<Vec<u8>>)> {
-        let (in_tx, in_rx) = mpsc::channel::<Vec<u8>>();
-        let (out_tx, out_rx) = mpsc::channel::<Vec<u8>>();
+    /// Connect to the ports matching `name`, calling `on_input` from midir's thread
+    /// with each message and when it arrived.
+    pub fn connect(name: &str, mut on_input: impl FnMut(Instant, &[u8]) + Send + 'static) -> Result<Self> {
+        let midi_in = midir::MidiInput::new(&format!("{}_in", name))?;
+        let in_port = find_port(&midi_in, name).with_context(|| format!("no midi input '{name}'"))?;
+        let mut raw = Self::connect_output(name)?;
+
+        let in_conn = midi_in
+            .connect(&in_port, "in", move |_, data, _| on_input(Instant::now(), data), ())
+            .map_err(|e| anyhow!("failed to connect midi input '{name}': {e}"))?;
+        raw._in_conn = Some(in_conn);
+        Ok(raw)
+    }
 
-        let midi_in = MidiInput::new(&format!("{}_in", name))?;
+    /// Connect to just the output port matching `name`.
+    pub fn connect_output(name: &str) -> Result<Self> {
         let midi_out = MidiOutput::new(&format!("{}_out", name))?;
+        let scan = MidiOutput::new(&format!("{}_scan", name))?;
 
-        let in_port = midi_in
-            .ports()
-            .into_iter()
-            .find(|p| midi_in.port_name(p).unwrap().contains(name))
-            .with_context(|| format!("no midi input '{name}'"))?;
-        let out_port = midi_out
-            .ports()
-            .into_iter()
-            .find(|p| midi_out.port_name(p).unwrap().contains(name))
-            .with_context(|| format!("no midi output '{name}'"))?;
-
-        let _name = name.to_string();
-        let _in_conn = midi_in
-            .connect(
-                &in_port,
-                "in",
-                move |_, data, _| {
-                    // trace!("{_name} <- [{data:X?}]");
-                    in_tx.send(data.to_vec()).unwrap();
-                },
-                (),
-            )
-            .unwrap();
-
-        let mut out_conn = midi_out.connect(&out_port, "out").unwrap();
-
-        let _name = name.to_string();
-        let _out_thread = thread::spawn(move || {
-            loop {
-                let data = out_rx.recv().unwrap();
-                // trace!("{_name} -> {data:X?}");
-                out_conn.send(&data).unwrap();
-            }
-        });
+        let out_port = find_port(&midi_out, name).with_context(|| format!("no midi output '{name}'"))?;
+        let out_conn = midi_out
+            .connect(&out_port, "out")
+            .map_err(|e| anyhow!("failed to connect midi output '{name}': {e}"))?;
+
+        Ok(Self { name: name.to_string(), scan, out_conn, _in_conn: None })
+    }
+
+    pub fn send(&mut self, data: &[u8]) -> Result<()> {
+        self.out_conn.send(data)?;
+        Ok(())
+    }
 
-        Ok((Self { _in_conn, _out_thread }, in_rx, out_tx))
+    /// Whether the device's ports are still around.
+    pub fn present(&self) -> bool {
+        find_port(&self.scan, &self.name).is_some()
     }
 }
+
+fn find_port<T: MidiIO>(io: &T, name: &str) -> Option<T::Port> {
+    io.ports().into_iter().find(|p| io.port_name(p).is_ok_and(|n| n.contains(name)))
+}
@@ -35,7 +35,9 @@ impl Plugin for RavyPlugin {
         .add_plugins(super::audio::AudioPlugin)
         .add_plugins(super::ui::UiPlugin)
         .add_plugins(super::sim::SimPlugin)
+        .add_plugins(super::dmx::DmxPlugin)
         .add_plugins(super::lights::LightsPlugin { models })
+        .add_plugins(super::laser::LaserPlugin)
         .add_systems(PreUpdate, hotkeys);
     }
 }
@@ -0,0 +1,24 @@
+use bevy_egui::egui::{self, RichText};
+
+use crate::prelude::*;
+
+pub fn draw(ui: &mut egui::Ui, world: &mut World) {
+    let status = world.get_resource::<MidiStatus>();
+    let mut devices = status.into_iter().flat_map(|status| status.iter()).peekable();
+    if devices.peek().is_none() {
+        ui.label(RichText::new("No MIDI devices").weak());
+        return;
+    }
+
+    for (name, connected) in devices {
+        ui.horizontal(|ui| {
+            let (color, state) = match connected {
+                true => (egui::Color32::from_rgb(88, 230, 144), "connected"),
+                false => (egui::Color32::from_rgb(255, 80, 80), "disconnected"),
+            };
+            ui.label(RichText::new("●").color(color));
+            ui.label(RichText::new(name).monospace());
+            ui.label(RichText::new(state).weak());
+        });
+    }
+}
@@ -2,6 +2,7 @@ use crate::prelude::*;
 
 mod audio_inspector;
 mod inspector;
+mod midi_inspector;
 mod ui;
 mod utils;
 pub mod widgets;
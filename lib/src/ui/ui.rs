@@ -22,6 +22,7 @@ pub enum Tab {
     Entities,
     Inspector,
     Audio,
+    Midi,
     Resources,
 }
 
@@ -31,7 +32,7 @@ impl Default for Ui {
         let tree = dock.main_surface_mut();
         let [_game, hierarchy] = tree.split_left(NodeIndex::root(), 0.2, vec![Tab::Entities, Tab::Resources]);
         let [_hierarchy, inspector] = tree.split_below(hierarchy, 0.25, vec![Tab::Inspector]);
-        let [_inspector, _other] = tree.split_below(inspector, 0.5, vec![Tab::Audio]);
+        let [_inspector, _other] = tree.split_below(inspector, 0.5, vec![Tab::Audio, Tab::Midi]);
 
         Self {
             dock: Some(dock),
@@ -63,6 +64,7 @@ impl egui_dock::TabViewer for TabViewer<'_> {
             Tab::Entities  => inspector::draw_entities(egui, world, &types, ui),
             Tab::Inspector => inspector::draw(egui, world, &types, ui),
             Tab::Audio     => audio_inspector::draw(egui, world),
+            Tab::Midi      => midi_inspector::draw(egui, world),
             Tab::Resources => inspector::draw_resources(egui, &types, ui),
         }
     }
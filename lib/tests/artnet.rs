@@ -0,0 +1,72 @@
+use std::net::{Ipv4Addr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use lib::prelude::*;
+
+/// Build an ArtPollReply from a node at `ip`.
+fn poll_reply(ip: [u8; 4], short_name: &str, long_name: &str) -> Vec<u8> {
+    let mut reply = vec![0u8; 239];
+    reply[..8].copy_from_slice(b"Art-Net\0");
+    reply[8..10].copy_from_slice(&[0x00, 0x21]);
+    reply[10..14].copy_from_slice(&ip);
+    reply[26..26 + short_name.len()].copy_from_slice(short_name.as_bytes());
+    reply[44..44 + long_name.len()].copy_from_slice(long_name.as_bytes());
+    reply
+}
+
+#[test]
+fn loopback() {
+    // Stand in for a node.
+    let node = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    node.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+    let sock = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let mut artnet = ArtNet::with_socket(sock, node.local_addr().unwrap()).unwrap();
+    let mut buf = [0u8; 1024];
+
+    artnet.send_universe(1, &[0, 1, 2, 3]);
+    // Only the null start code is sent.
+    artnet.send_universe(1, &[0xCC, 1, 2]);
+    artnet.send_universe(1, &[0, 4, 5]);
+    artnet.send_universe(0x4321, &[0xFF; 513]);
+
+    let (size, _) = node.recv_from(&mut buf).unwrap();
+    assert_eq!(buf[..8], *b"Art-Net\0");
+    assert_eq!(buf[8..10], [0x00, 0x50]); // OpDmx
+    assert_eq!(buf[10..12], [0, 14]); // protocol version
+    assert_eq!(buf[12], 1); // sequence
+    assert_eq!(buf[14..16], [0, 0]); // port address
+    assert_eq!(buf[16..18], [0, 4]); // length, padded to even
+    assert_eq!(buf[18..size], [1, 2, 3, 0]);
+
+    let (size, _) = node.recv_from(&mut buf).unwrap();
+    assert_eq!(buf[12], 2);
+    assert_eq!(buf[16..18], [0, 2]);
+    assert_eq!(buf[18..size], [4, 5]);
+
+    // Sequences are per universe.
+    let (size, _) = node.recv_from(&mut buf).unwrap();
+    assert_eq!(size, 18 + 512);
+    assert_eq!(buf[12], 1);
+    assert_eq!(buf[14..16], [0x20, 0x43]);
+    assert_eq!(buf[16..18], [0x02, 0x00]);
+    assert!(buf[18..size].iter().all(|&b| b == 0xFF));
+
+    artnet.poll();
+    let (size, addr) = node.recv_from(&mut buf).unwrap();
+    assert_eq!(buf[..size], [b"Art-Net\0".as_slice(), &[0x00, 0x20, 0, 14, 0, 0][..]].concat());
+
+    // Anything but a full ArtPollReply is ignored.
+    node.send_to(&buf[..size], addr).unwrap();
+    node.send_to(&poll_reply([10, 0, 0, 8], "Short", "")[..100], addr).unwrap();
+    node.send_to(&poll_reply([10, 0, 0, 7], "Node", "Long name"), addr).unwrap();
+
+    let start = Instant::now();
+    while artnet.nodes().is_empty() && start.elapsed() < Duration::from_secs(1) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let nodes = artnet.nodes();
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(nodes[0].ip, Ipv4Addr::new(10, 0, 0, 7));
+    assert_eq!(nodes[0].short_name, "Node");
+    assert_eq!(nodes[0].long_name, "Long name");
+}
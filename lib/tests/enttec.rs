@@ -0,0 +1,42 @@
+// Pseudo-terminals are unix only.
+#![cfg(unix)]
+
+use std::io::Read;
+
+use lib::prelude::*;
+use serialport::TTYPort;
+
+/// Read one message from the other end of the pseudo-terminal.
+fn read_message(port: &mut TTYPort, len: usize) -> Vec<u8> {
+    let mut buf = vec![0; len];
+    port.read_exact(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn send_dmx() {
+    let (mut widget, port) = TTYPort::pair().unwrap();
+    let mut dmx = EnttecPro::with_port(Box::new(port), 1);
+
+    // Other universes are ignored.
+    dmx.send_universe(2, &[0, 9, 9, 9]);
+    // Short frames are padded out to 24 channels.
+    dmx.send_universe(1, &[0, 1, 2, 255]);
+
+    let msg = read_message(&mut widget, 30);
+    assert_eq!(msg[..4], [0x7E, 6, 25, 0]);
+    assert_eq!(msg[4..9], [0, 1, 2, 255, 0]);
+    assert!(msg[9..29].iter().all(|&b| b == 0));
+    assert_eq!(msg[29], 0xE7);
+
+    let mut frame = [0u8; 513];
+    for (i, ch) in frame.iter_mut().enumerate().skip(1) {
+        *ch = (i % 8) as u8;
+    }
+    dmx.send_universe(1, &frame);
+
+    let msg = read_message(&mut widget, 518);
+    assert_eq!(msg[..4], [0x7E, 6, 0x01, 0x02]);
+    assert_eq!(msg[4..517], frame);
+    assert_eq!(msg[517], 0xE7);
+}
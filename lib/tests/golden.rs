@@ -0,0 +1,220 @@
+use lib::dmx::device::bar_rgb_18w::Bar;
+use lib::dmx::device::beam_rgbw_60w::{Beam, BeamMode, BeamRing};
+use lib::dmx::device::beam_rgbw_90w::BigBeam;
+use lib::dmx::device::gobo_60w::Gobo;
+use lib::dmx::device::laser_array::{self, LaserArray};
+use lib::dmx::device::laser_scan_30w::{self, Laser, LaserPattern, LaserStroke};
+use lib::dmx::device::par_rgbw_12x3w::Par;
+use lib::dmx::device::spider_rgbw_8x10w::Spider;
+use lib::dmx::device::strobe_rgb_35w::Strobe;
+use lib::dmx::golden::{assert_encodes, assert_round_trips, render};
+use lib::lights::fixture::{SaberSpot, StealthBeam};
+use lib::prelude::*;
+
+#[test]
+fn bar_rgb_18w() {
+    assert_encodes(&Bar::default(), &[0; 7]);
+    assert_encodes(
+        &Bar { color: Rgbw(1.0, 0.5, 0.25, 0.0), alpha: 0.75 },
+        &[255, 127, 63, 0, 0, 0, 191],
+    );
+}
+
+#[test]
+fn beam_rgbw_60w() {
+    // Default yaw of 0.33 is 0x547A
+    assert_encodes(&Beam::default(), &[84, 122, 0, 0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_encodes(
+        &Beam {
+            mode: BeamMode::ColorCycle,
+            ring: BeamRing::BlueTeal,
+            pitch: 0.5,
+            yaw: 0.75,
+            speed: 0.25,
+            color: Rgbw(1.0, 0.0, 0.5, 0.25),
+            alpha: 0.5,
+            // Not sent.
+            shutter: Shutter::Strobe(0.5),
+        },
+        &[191, 255, 127, 255, 191, 127, 0, 255, 0, 127, 63, 0, 159, 0, 216],
+    );
+    assert_encodes(
+        &Beam { shutter: Shutter::Closed, ..Default::default() },
+        &[84, 122, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+    );
+}
+
+#[test]
+fn beam_rgbw_90w() {
+    assert_encodes(&BigBeam::default(), &[84, 255, 0, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_encodes(
+        &BigBeam {
+            pitch: 0.25,
+            yaw: 0.5,
+            speed: 0.5,
+            color: Rgbw(0.0, 1.0, 0.0, 0.5),
+            alpha: 0.75,
+            strobe: 1.0,
+        },
+        &[127, 191, 127, 191, 255, 0, 255, 0, 127, 0, 0, 0, 0],
+    );
+}
+
+#[test]
+fn gobo_60w() {
+    assert_encodes(&Gobo::default(), &[0; 9]);
+    assert_encodes(
+        &Gobo {
+            pan: 0.5,
+            tilt: 0.25,
+            color: 0.75,
+            pattern: 1.0,
+            strobe: 0.0,
+            alpha: 1.0,
+            speed: 0.5,
+            auto: 0.0,
+        },
+        &[127, 63, 191, 255, 0, 255, 127, 0, 0],
+    );
+}
+
+#[test]
+fn laser_array() {
+    assert_encodes(&LaserArray::default(), &[102, 0, 0, 255, 188]);
+    assert_encodes(
+        &LaserArray { angle: 0.5, brightness: 0.5, color: laser_array::LaserColor::Blue },
+        &[117, 0, 0, 127, 68],
+    );
+}
+
+#[test]
+fn laser_scan_30w() {
+    assert_encodes(&Laser::default(), &[0, 0, 0, 0, 0, 0, 0, 0, 64, 0]);
+    assert_encodes(
+        &Laser {
+            on: true,
+            pattern: LaserPattern::Heart,
+            color: laser_scan_30w::LaserColor::GREEN,
+            stroke: LaserStroke::Dots(0.5),
+            rotate: 0.5,
+            xflip: 0.0,
+            yflip: 1.0,
+            x: 0.25,
+            y: 0.75,
+            size: 0.5,
+        },
+        &[64, 122, 63, 127, 0, 31, 95, 31, 98, 191],
+    );
+}
+
+#[test]
+fn par_rgbw_12x3w() {
+    assert_encodes(&Par::default(), &[0, 0, 0, 255, 0, 0, 0, 0]);
+    assert_encodes(&Par { color: Rgbw(0.25, 0.5, 0.75, 1.0) }, &[0, 0, 0, 255, 63, 127, 191, 255]);
+}
+
+#[test]
+fn spider_rgbw_8x10w() {
+    assert_encodes(&Spider::default(), &[0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_encodes(
+        &Spider {
+            alpha: 0.5,
+            color0: Rgbw(1.0, 0.0, 0.0, 0.0),
+            pos0: 0.25,
+            color1: Rgbw(0.0, 0.5, 0.0, 1.0),
+            pos1: 0.75,
+        },
+        &[63, 191, 127, 0, 255, 0, 0, 0, 0, 127, 0, 255, 0, 0, 0],
+    );
+}
+
+#[test]
+fn strobe_rgb_35w() {
+    assert_encodes(&Strobe::default(), &[255, 0, 0, 0, 0, 0]);
+    assert_encodes(&Strobe { color: Rgb(0.5, 1.0, 0.25), alpha: 0.75 }, &[191, 0, 127, 255, 63, 0]);
+}
+
+#[test]
+fn adj_stealth_beam() {
+    assert_encodes(
+        &StealthBeam::default(),
+        &[0, 0, 127, 255, 0, 0, 0, 0, 0, 255, 255, 0, 0, 0, 0, 0],
+    );
+    assert_encodes(
+        &StealthBeam {
+            pitch: 1.0,
+            yaw: 0.25,
+            color: Rgbw(0.5, 0.25, 0.0, 1.0),
+            alpha: 0.75,
+            shutter: Shutter::Strobe(1.0),
+        },
+        &[63, 255, 255, 255, 127, 63, 0, 255, 0, 191, 95, 0, 0, 0, 0, 0],
+    );
+    assert_encodes(
+        &StealthBeam { shutter: Shutter::Closed, ..Default::default() },
+        &[0, 0, 127, 255, 0, 0, 0, 0, 0, 255, 0, 0, 0, 0, 0, 0],
+    );
+}
+
+#[test]
+fn adj_saber_spot() {
+    assert_encodes(&SaberSpot::default(), &[0, 0, 0, 0, 255, 255, 0, 0]);
+    assert_encodes(
+        &SaberSpot { color: Rgbw(1.0, 0.5, 0.0, 0.25), alpha: 0.5, shutter: Shutter::Strobe(0.0) },
+        &[255, 127, 0, 63, 64, 127, 0, 0],
+    );
+}
+
+#[test]
+fn round_trip() {
+    assert_round_trips(&Bar { color: Rgbw(1.0, 0.5, 0.25, 0.0), alpha: 0.75 });
+    assert_round_trips(&Beam {
+        mode: BeamMode::ColorCycle,
+        ring: BeamRing::BlueTeal,
+        pitch: 0.5,
+        yaw: 0.75,
+        color: Rgbw(1.0, 0.0, 0.5, 0.25),
+        ..Default::default()
+    });
+    assert_round_trips(&Gobo {
+        pan: 0.5,
+        tilt: 0.25,
+        color: 0.75,
+        pattern: 1.0,
+        alpha: 1.0,
+        ..Default::default()
+    });
+    assert_round_trips(&Par { color: Rgbw(0.25, 0.5, 0.75, 1.0) });
+    assert_round_trips(&Strobe { color: Rgb(0.5, 1.0, 0.25), alpha: 0.75 });
+    for shutter in [Shutter::Open, Shutter::Closed, Shutter::Strobe(1.0)] {
+        assert_round_trips(&StealthBeam {
+            pitch: 1.0,
+            yaw: 0.25,
+            color: Rgbw(0.5, 0.25, 0.0, 1.0),
+            alpha: 0.75,
+            shutter,
+        });
+        assert_round_trips(&SaberSpot { color: Rgbw(1.0, 0.5, 0.0, 0.25), alpha: 0.5, shutter });
+    }
+}
+
+/// Writes one channel more than it claims to have.
+struct Overrun;
+
+impl DmxDevice for Overrun {
+    fn channels(&self) -> usize {
+        2
+    }
+
+    fn encode(&self, buf: &mut [u8]) {
+        if let Some(b) = buf.get_mut(2) {
+            *b = 1;
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "past its 2 channels")]
+fn overrun() {
+    render(&Overrun);
+}
@@ -0,0 +1,112 @@
+use lib::laser::{LaserFrame, LaserOutput, LaserPoint, parse_ilda, write_ilda};
+use lib::prelude::*;
+
+fn point(x: f32, y: f32, color: Rgb, blank: bool) -> LaserPoint {
+    LaserPoint { pos: Vec2::new(x, y), color, blank }
+}
+
+/// Header of a section with `count` records in `format`.
+fn header(format: u8, count: u16) -> Vec<u8> {
+    let mut data = b"ILDA\0\0\0".to_vec();
+    data.push(format);
+    data.extend([0; 16]);
+    data.extend(count.to_be_bytes());
+    data.extend([0; 6]);
+    data
+}
+
+#[test]
+fn round_trip() {
+    let frames = vec![
+        LaserFrame {
+            points: vec![
+                point(-1.0, -1.0, Rgb::BLACK, true),
+                point(1.0, -1.0, Rgb(1.0, 0.0, 0.0), false),
+                point(1.0, 1.0, Rgb(0.0, 1.0, 0.0), false),
+                point(-1.0, 1.0, Rgb(0.0, 0.0, 1.0), false),
+            ],
+        },
+        LaserFrame { points: vec![point(0.0, 0.0, Rgb::WHITE, false)] },
+    ];
+
+    let data = write_ilda(&frames);
+    assert_eq!(&data[..4], b"ILDA");
+    assert_eq!(parse_ilda(&data).unwrap(), frames);
+
+    // Empty frames come back blanked rather than ending the file early.
+    let with_empty = [frames[0].clone(), LaserFrame::default(), frames[1].clone()];
+    let blank = LaserFrame { points: vec![point(0.0, 0.0, Rgb::BLACK, true)] };
+    assert_eq!(
+        parse_ilda(&write_ilda(&with_empty)).unwrap(),
+        [frames[0].clone(), blank, frames[1].clone()]
+    );
+}
+
+#[test]
+fn indexed() {
+    // Format 1: x, y, status, palette index.
+    let mut data = header(1, 2);
+    data.extend([0x80, 0x01, 0x00, 0x00, 0b0100_0000, 0]);
+    data.extend([0x7f, 0xff, 0x00, 0x00, 0b1000_0000, 24]);
+    data.extend(header(1, 0));
+
+    let frames = parse_ilda(&data).unwrap();
+    assert_eq!(
+        frames,
+        [LaserFrame {
+            points: vec![
+                point(-1.0, 0.0, Rgb(1.0, 0.0, 0.0), true),
+                point(1.0, 0.0, Rgb(0.0, 1.0, 0.0), false),
+            ],
+        }]
+    );
+    assert_eq!(frames[0].segments().count(), 1);
+}
+
+#[test]
+fn palette() {
+    // Format 2 replaces the palette for the frames after it.
+    let mut data = header(2, 1);
+    data.extend([255, 0, 255]);
+    data.extend(header(0, 1));
+    data.extend([0, 0, 0, 0, 0, 0, 0b1000_0000, 0]);
+
+    let frames = parse_ilda(&data).unwrap();
+    assert_eq!(
+        frames,
+        [LaserFrame { points: vec![point(0.0, 0.0, Rgb(1.0, 0.0, 1.0), false)] }]
+    );
+}
+
+#[test]
+fn invalid() {
+    assert!(parse_ilda(b"ILDB").is_err());
+    assert!(parse_ilda(&header(5, 0)[..16]).is_err());
+    assert!(parse_ilda(&header(3, 1)).is_err());
+
+    // Missing the second record.
+    let mut data = header(5, 2);
+    data.extend([0; 8]);
+    assert!(parse_ilda(&data).is_err());
+}
+
+#[derive(Default)]
+struct Sent(Vec<LaserFrame>);
+impl LaserOutput for Sent {
+    fn send_frame(&mut self, frame: &LaserFrame) {
+        self.0.push(frame.clone());
+    }
+}
+
+#[test]
+fn output() {
+    let frame = LaserFrame { points: vec![point(0.5, 0.5, Rgb::WHITE, false)] };
+
+    let (mut a, mut b) = (Sent::default(), Sent::default());
+    let mut out: Box<dyn LaserOutput + '_> = Box::new((&mut a, &mut b));
+    out.send_frame(&frame);
+    drop(out);
+
+    assert_eq!(a.0, [frame.clone()]);
+    assert_eq!(b.0, [frame]);
+}
@@ -0,0 +1,73 @@
+use lib::dmx::{DmxMerge, MergeMode, UNIVERSE_SIZE};
+
+#[test]
+fn htp() {
+    let mut merge = DmxMerge::default();
+    merge.source("a").write(1, 1, &[10, 200]);
+    merge.source("b").write(1, 1, &[100, 50]);
+
+    assert_eq!(merge.frame(1)[..4], [0, 100, 200, 0]);
+    assert_eq!(merge.universes(), [1]);
+    assert_eq!(merge.frame(2), [0; UNIVERSE_SIZE]);
+}
+
+#[test]
+fn ltp() {
+    let mut merge = DmxMerge::default();
+    merge.set_mode(1, 1..3, MergeMode::Ltp);
+    merge.source("a").write(1, 1, &[10, 200, 9]);
+    merge.source("b").write(1, 1, &[100, 50, 7]);
+    // Channel 3 is still HTP.
+    assert_eq!(merge.frame(1)[1..4], [100, 50, 9]);
+
+    // Resending the same values doesn't take the channels back.
+    merge.source("a").write(1, 1, &[10, 200]);
+    assert_eq!(merge.frame(1)[1..3], [100, 50]);
+
+    merge.source("a").set(1, 2, 201);
+    assert_eq!(merge.frame(1)[1..3], [100, 201]);
+}
+
+#[test]
+fn priority() {
+    let mut merge = DmxMerge::default();
+    merge.set_mode(1, 1..3, MergeMode::Ltp);
+    merge.source("override").set_priority(150).write(1, 1, &[10, 20]);
+    merge.source("preset").write(1, 1, &[255, 255]);
+    merge.source("preset").set(1, 3, 255);
+    merge.source("override").set(1, 3, 30);
+
+    // Higher priority wins regardless of mode, but only on channels it wrote.
+    merge.source("override").release(1, 2..3);
+    assert_eq!(merge.frame(1)[1..4], [10, 255, 30]);
+}
+
+#[test]
+fn release() {
+    let mut merge = DmxMerge::default();
+    merge.source("a").write(1, 1, &[10, 20]);
+    merge.source("b").set_priority(150).write(1, 1, &[30, 40]);
+    assert_eq!(merge.frame(1)[1..3], [30, 40]);
+
+    merge.source("b").release(1, 1..2);
+    assert_eq!(merge.frame(1)[1..3], [10, 40]);
+
+    merge.source("a").release(1, 1..3);
+    assert_eq!(merge.frame(1)[1..3], [0, 40]);
+
+    merge.remove("b");
+    assert_eq!(merge.frame(1)[1..3], [0, 0]);
+}
+
+#[test]
+fn out_of_range() {
+    let mut merge = DmxMerge::default();
+    merge.set_mode(1, 511..600, MergeMode::Ltp);
+    merge.set_mode(1, 600..700, MergeMode::Ltp);
+    merge.source("a").write(1, 511, &[100, 200, 1, 2]);
+    merge.source("b").write(1, 511, &[5, 6]);
+    assert_eq!(merge.frame(1)[511..], [5, 6]);
+
+    merge.source("b").release(1, 512..1000).release(1, 1000..2000);
+    assert_eq!(merge.frame(1)[511..], [5, 200]);
+}
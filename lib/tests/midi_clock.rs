@@ -0,0 +1,93 @@
+use std::sync::mpsc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use lib::midi::MidiDevice;
+use lib::midi::clock::{ClockMessage, ClockOut, ClockSync, MidiClock, PPQN};
+
+/// Feed `n` pulses at `bpm` starting at `t`, each up to a millisecond late.
+fn pulses(clock: &mut ClockSync, t: Instant, bpm: f64, n: u32) -> Instant {
+    let period = 60.0 / (bpm * PPQN as f64);
+    for i in 0..n {
+        // Deterministic stand-in for jitter.
+        let jitter = ((i * 7919) % 11) as f64 / 10.0 * 0.001;
+        let at = t + Duration::from_secs_f64(i as f64 * period + jitter);
+        clock.handle(ClockMessage::Tick, at);
+    }
+    t + Duration::from_secs_f64(n as f64 * period)
+}
+
+#[test]
+fn messages() {
+    let mut clock = MidiClock;
+    for message in [
+        ClockMessage::Tick,
+        ClockMessage::Start,
+        ClockMessage::Continue,
+        ClockMessage::Stop,
+        ClockMessage::SongPosition(0x1234),
+    ] {
+        let raw = clock.process_output(message);
+        assert_eq!(clock.process_input(&raw), Some(message));
+    }
+    assert_eq!(clock.process_output(ClockMessage::SongPosition(130)), [0xF2, 2, 1]);
+    assert_eq!(clock.process_input(&[0x90, 60, 127]), None);
+}
+
+#[test]
+fn tempo() {
+    let mut clock = ClockSync::new();
+    let t = Instant::now();
+    assert_eq!(clock.bpm(), None);
+
+    pulses(&mut clock, t, 128.0, 4 * PPQN);
+    let bpm = clock.bpm().unwrap();
+    assert!((bpm - 128.0).abs() < 0.5, "bpm={bpm}");
+    // Not playing until started.
+    assert_eq!(clock.beat(t), None);
+}
+
+#[test]
+fn position() {
+    let mut clock = ClockSync::new();
+    let t = Instant::now();
+
+    clock.handle(ClockMessage::Start, t);
+    let end = pulses(&mut clock, t, 120.0, 2 * PPQN);
+    let beat = clock.beat(end).unwrap();
+    assert!((beat - 2.0).abs() < 0.05, "beat={beat}");
+
+    // Holds a beat past the last pulse when the clock goes away.
+    let beat = clock.beat(end + Duration::from_secs(10)).unwrap();
+    assert!((beat - 2.96).abs() < 0.05, "beat={beat}");
+
+    // Song position is in 16th notes.
+    clock.handle(ClockMessage::Stop, end);
+    assert_eq!(clock.beat(end), None);
+    clock.handle(ClockMessage::SongPosition(16), end);
+    clock.handle(ClockMessage::Continue, end);
+    pulses(&mut clock, end, 120.0, 1);
+    let beat = clock.beat(end).unwrap();
+    assert!((beat - 4.0).abs() < 0.05, "beat={beat}");
+}
+
+#[test]
+fn clock_out() {
+    let (tx, rx) = mpsc::channel();
+    // 10 ms pulses.
+    let clock_out = ClockOut::new(250.0, move |message| tx.send(message).unwrap());
+
+    // Nothing until it's started.
+    sleep(Duration::from_millis(50));
+    assert_eq!(rx.try_iter().count(), 0);
+
+    clock_out.start();
+    sleep(Duration::from_millis(55));
+    clock_out.stop();
+    sleep(Duration::from_millis(20));
+    let messages = rx.try_iter().collect::<Vec<_>>();
+    assert_eq!(messages[0], ClockMessage::Start);
+    let stop = messages.iter().position(|&m| m == ClockMessage::Stop).unwrap();
+    assert!(messages[1..stop].iter().all(|&m| m == ClockMessage::Tick));
+    assert!((3..=8).contains(&(stop - 1)), "ticks={}", stop - 1);
+}
@@ -0,0 +1,90 @@
+use lib::midi::device::generic::{Control, GenericMidi, Message};
+use lib::midi::{MidiDevice, MidiLearn};
+
+#[test]
+fn messages() {
+    let mut midi = GenericMidi;
+    let mut input = |raw: &[u8]| midi.process_input(raw);
+
+    assert_eq!(
+        input(&[0x91, 36, 127]),
+        Some(Message::Note { channel: 1, note: 36, velocity: 1.0 })
+    );
+    assert_eq!(
+        input(&[0x81, 36, 64]),
+        Some(Message::Note { channel: 1, note: 36, velocity: 0.0 })
+    );
+    assert_eq!(input(&[0xB0, 7, 0]), Some(Message::Cc { channel: 0, cc: 7, value: 0.0 }));
+    assert_eq!(input(&[0xEF, 0, 64]), Some(Message::PitchBend { channel: 15, value: 0.0 }));
+    assert_eq!(input(&[0xE0, 0, 0]), Some(Message::PitchBend { channel: 0, value: -1.0 }));
+    assert_eq!(
+        input(&[0xA2, 60, 127]),
+        Some(Message::PolyPressure { channel: 2, note: 60, value: 1.0 })
+    );
+    assert_eq!(input(&[0xD3, 127]), Some(Message::Pressure { channel: 3, value: 1.0 }));
+    assert_eq!(input(&[0xC4, 5]), Some(Message::Program { channel: 4, program: 5 }));
+
+    // Clock, and truncated messages.
+    assert_eq!(input(&[0xF8]), None);
+    assert_eq!(input(&[0xB0, 7]), None);
+
+    assert_eq!(
+        midi.process_output(Message::Note { channel: 1, note: 36, velocity: 1.0 }),
+        [0x91, 36, 127]
+    );
+    assert_eq!(
+        midi.process_output(Message::PitchBend { channel: 0, value: 0.0 }),
+        [0xE0, 0, 64]
+    );
+    assert_eq!(
+        midi.process_output(Message::PitchBend { channel: 0, value: 1.0 }),
+        [0xE0, 127, 127]
+    );
+}
+
+#[test]
+fn learn() {
+    let mut learn = MidiLearn::new();
+    let fader = |value| Message::Cc { channel: 0, cc: 7, value };
+    let pad = |velocity| Message::Note { channel: 9, note: 36, velocity };
+
+    // Nothing bound yet.
+    assert_eq!(learn.handle(fader(0.5)), None);
+
+    learn.learn("brightness");
+    assert_eq!(learn.learning(), Some("brightness"));
+    assert_eq!(learn.handle(fader(0.5)), Some(("brightness", 0.5)));
+    assert_eq!(learn.learning(), None);
+    assert_eq!(learn.value("brightness"), Some(0.5));
+
+    // Pads are learned on press, not release.
+    learn.learn("strobe");
+    assert_eq!(learn.handle(pad(0.0)), None);
+    assert_eq!(learn.handle(pad(1.0)), Some(("strobe", 1.0)));
+    assert_eq!(learn.handle(pad(0.0)), Some(("strobe", 0.0)));
+
+    // Rebinding a control moves it to the new parameter.
+    learn.learn("speed");
+    learn.handle(fader(0.25));
+    assert_eq!(learn.binding("brightness"), None);
+    assert_eq!(learn.binding("speed"), Some(Control::Cc { channel: 0, cc: 7 }));
+}
+
+#[test]
+fn save() {
+    let path = std::env::temp_dir().join(format!("ravy-midi-learn-{}.ron", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut learn = MidiLearn::load(&path).unwrap();
+    learn.bind("strobe", Control::Note { channel: 9, note: 36 });
+    learn.bind("bend", Control::PitchBend { channel: 0 });
+    learn.save().unwrap();
+
+    let learn = MidiLearn::load(&path).unwrap();
+    assert_eq!(learn.binding("strobe"), Some(Control::Note { channel: 9, note: 36 }));
+    assert_eq!(learn.binding("bend"), Some(Control::PitchBend { channel: 0 }));
+
+    std::fs::write(&path, "not ron").unwrap();
+    assert!(MidiLearn::load(&path).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
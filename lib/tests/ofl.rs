@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use lib::dmx::{FixtureProfile, ProfileFixture, attr};
+use lib::prelude::*;
+
+const STEALTH_BEAM: &str = include_str!("ofl/adj-stealth-beam.json");
+const GOBO: &str = include_str!("ofl/gobo-60w.json");
+
+#[test]
+fn stealth_beam_channels() {
+    let profile = FixtureProfile::from_ofl(STEALTH_BEAM, Some("16ch")).unwrap();
+    assert_eq!(profile.name, "Stealth Beam (16-channel)");
+    assert_eq!(profile.footprint(), 16);
+
+    let offsets = |attr| profile.channel(attr).map(|ch| (ch.offset, ch.fine));
+    assert_eq!(offsets(attr::YAW), Some((0, Some(1))));
+    assert_eq!(offsets(attr::PITCH), Some((2, Some(3))));
+    assert_eq!(offsets(attr::RED), Some((4, None)));
+    assert_eq!(offsets(attr::WHITE), Some((7, None)));
+    assert_eq!(offsets("color_macros"), Some((8, None)));
+    assert_eq!(offsets(attr::ALPHA), Some((9, None)));
+    assert_eq!(offsets(attr::STROBE), Some((10, None)));
+    assert_eq!(offsets(attr::SPEED), Some((11, None)));
+
+    let strobe = profile.channel(attr::STROBE).unwrap();
+    assert_eq!(strobe.range, Some((64, 95)));
+    assert_eq!(strobe.slots.get("closed"), Some(&0));
+    assert_eq!(strobe.slots.get("open"), Some(&32));
+
+    assert!(profile.channel(attr::SPEED).unwrap().invert);
+}
+
+#[test]
+fn stealth_beam_modes() {
+    let profile = FixtureProfile::from_ofl(STEALTH_BEAM, Some("8-channel")).unwrap();
+    assert_eq!(profile.footprint(), 8);
+    assert_eq!(profile.channel(attr::YAW).map(|ch| (ch.offset, ch.fine)), Some((0, None)));
+
+    let first = FixtureProfile::from_ofl(STEALTH_BEAM, None).unwrap();
+    assert_eq!(first.footprint(), 16);
+
+    assert!(FixtureProfile::from_ofl(STEALTH_BEAM, Some("3ch")).is_err());
+}
+
+#[test]
+fn stealth_beam_encode() {
+    let profile = FixtureProfile::from_ofl(STEALTH_BEAM, Some("16ch")).unwrap();
+    let mut fixture = ProfileFixture::new(Arc::new(profile)).unwrap();
+    assert_eq!(fixture.pitch(), 0.5);
+    assert_eq!(fixture.get(attr::ALPHA), Some(1.0));
+
+    fixture.set(attr::YAW, 1.0).set_color(Rgbw(1.0, 0.0, 0.0, 0.0));
+    fixture.set_slot(attr::STROBE, "open").unwrap();
+
+    let mut buf = [0xAA; 16];
+    fixture.encode(&mut buf);
+    assert_eq!(buf[..12], [255, 255, 127, 255, 255, 0, 0, 0, 0, 255, 32, 255]);
+
+    fixture.set(attr::STROBE, 1.0);
+    fixture.encode(&mut buf);
+    assert_eq!(buf[10], 95);
+
+    // Out of range values are clamped rather than wrapping into other slots.
+    fixture.set(attr::STROBE, 1.5);
+    fixture.encode(&mut buf);
+    assert_eq!(buf[10], 95);
+
+    assert!(fixture.set_slot(attr::STROBE, "fast").is_err());
+    assert!(fixture.set_slot(attr::GOBO, "open").is_err());
+}
+
+#[test]
+fn gobo_wheels() {
+    let profile = FixtureProfile::from_ofl(GOBO, None).unwrap();
+    assert_eq!(profile.footprint(), 9);
+
+    let slots = |attr| {
+        let ch = profile.channel(attr).unwrap();
+        ch.slots.iter().map(|(name, &raw)| (name.as_str(), raw)).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        slots(attr::COLOR_WHEEL),
+        [
+            ("blue", 40),
+            ("deep_purple", 50),
+            ("green", 30),
+            ("open", 0),
+            ("red", 10)
+        ]
+    );
+    assert_eq!(
+        slots(attr::GOBO),
+        [
+            ("dots", 8),
+            ("gobo_3", 16),
+            ("gobo_shake", 32),
+            ("open", 0),
+            ("star", 24)
+        ]
+    );
+    assert_eq!(slots(attr::STROBE), [("open", 0)]);
+    assert_eq!(slots("reset"), [("reset", 250)]);
+
+    let mut fixture = ProfileFixture::new(Arc::new(profile)).unwrap();
+    fixture.set_slot(attr::COLOR_WHEEL, "deep_purple").unwrap();
+    fixture.set_slot(attr::GOBO, "star").unwrap();
+    fixture.set(attr::STROBE, 0.0).set(attr::ALPHA, 1.0);
+
+    let mut buf = [0; 9];
+    fixture.encode(&mut buf);
+    assert_eq!(buf, [0, 0, 50, 24, 10, 255, 255, 0, 0]);
+}
@@ -0,0 +1,30 @@
+use lib::dmx::DmxAddress;
+use lib::prelude::*;
+
+#[test]
+fn add() {
+    let mut patch = Patch::default();
+    let [a, b] = [Entity::from_raw(1), Entity::from_raw(2)];
+
+    patch.add(a, 1, 1, 16).unwrap();
+    patch.add(b, 1, 17, 16).unwrap();
+    assert_eq!(patch.get(b), Some(DmxAddress { universe: 1, address: 17 }));
+
+    // Overlapping
+    assert!(patch.add(b, 1, 16, 16).is_err());
+    assert_eq!(patch.get(b), Some(DmxAddress { universe: 1, address: 17 }));
+    patch.add(b, 2, 16, 16).unwrap();
+
+    // Out of range
+    assert!(patch.add(a, 0, 1, 16).is_err());
+    assert!(patch.add(a, 1, 0, 16).is_err());
+    assert!(patch.add(a, 1, 0, 0).is_err());
+    assert!(patch.add(a, 1, 498, 16).is_err());
+    assert!(patch.add(a, 1, usize::MAX, 16).is_err());
+    assert!(patch.add(a, 1, 1, usize::MAX).is_err());
+    patch.add(a, 1, 497, 16).unwrap();
+
+    assert_eq!(patch.remove(a), Some(DmxAddress { universe: 1, address: 497 }));
+    assert_eq!(patch.remove(a), None);
+    assert_eq!(patch.iter().count(), 1);
+}
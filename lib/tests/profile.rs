@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use lib::dmx::{ChannelProfile, FixtureProfile, ProfileFixture, attr};
+
+fn channel(attr: &str, offset: usize) -> ChannelProfile {
+    ChannelProfile {
+        attr: attr.to_string(),
+        offset,
+        fine: None,
+        range: None,
+        invert: false,
+        default: 0.0,
+        slots: Default::default(),
+    }
+}
+
+#[test]
+fn validate() {
+    let profile = FixtureProfile::parse(
+        r#"(name: "par", channels: [(attr: "alpha", offset: 0), (attr: "red", offset: 1, fine: Some(2))])"#,
+    )
+    .unwrap();
+    assert_eq!(profile.footprint(), 3);
+
+    // Same attribute twice.
+    let dup = r#"(name: "par", channels: [(attr: "red", offset: 0), (attr: "red", offset: 1)])"#;
+    assert!(FixtureProfile::parse(dup).is_err());
+    // Overlapping channels.
+    let overlap =
+        r#"(name: "par", channels: [(attr: "red", offset: 0, fine: Some(1)), (attr: "blue", offset: 1)])"#;
+    assert!(FixtureProfile::parse(overlap).is_err());
+    // Past the footprint.
+    let past = r#"(name: "par", footprint: Some(1), channels: [(attr: "red", offset: 1)])"#;
+    assert!(FixtureProfile::parse(past).is_err());
+}
+
+#[test]
+fn new() {
+    let mut profile = FixtureProfile {
+        name: "par".to_string(),
+        footprint: Some(2),
+        channels: vec![channel(attr::RED, 0), channel(attr::GREEN, 1)],
+    };
+    assert!(ProfileFixture::new(Arc::new(profile.clone())).is_ok());
+
+    // Built by hand rather than parsed, so it's never been validated.
+    profile.channels.push(channel(attr::BLUE, 5));
+    assert!(ProfileFixture::new(Arc::new(profile.clone())).is_err());
+    profile.channels[2] = channel(attr::RED, 1);
+    assert!(ProfileFixture::new(Arc::new(profile)).is_err());
+}
@@ -0,0 +1,129 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use lib::dmx::rdm::{
+    CommandClass, RdmController, RdmMessage, RdmResponder, RdmTransport, ResponseType, Uid, nack, pid,
+};
+
+const CONTROLLER: Uid = Uid::new(0x7FF0, 1);
+
+/// Simulated responders sharing a DMX line.
+#[derive(Clone)]
+struct Line {
+    responders: Rc<RefCell<Vec<RdmResponder>>>,
+    /// Whether simultaneous responses collide, like on a real line.
+    collide: bool,
+}
+
+impl Line {
+    fn new(uids: &[Uid], collide: bool) -> Self {
+        let responders = uids.iter().map(|&uid| RdmResponder::new(uid, 16, 1)).collect();
+        Self { responders: Rc::new(RefCell::new(responders)), collide }
+    }
+
+    fn responder(&self, uid: Uid) -> RdmResponder {
+        self.responders.borrow().iter().find(|r| r.uid == uid).unwrap().clone()
+    }
+}
+
+impl RdmTransport for Line {
+    fn send(&mut self, request: &[u8]) -> anyhow::Result<Vec<Vec<u8>>> {
+        let mut responders = self.responders.borrow_mut();
+        let mut responses = responders.iter_mut().filter_map(|r| r.handle(request)).collect::<Vec<_>>();
+        if self.collide && responses.len() > 1 {
+            responses = vec![vec![0; 24]];
+        }
+        Ok(responses)
+    }
+}
+
+#[test]
+fn discover() {
+    let uids = [
+        Uid::new(0x0001, 0xFFFF_0000),
+        Uid::new(0x7A70, 1),
+        Uid::new(0x7A70, 2),
+        Uid::new(0x7FFF, 0x1234_5678),
+    ];
+
+    // Colliding responses split the search until each device answers alone.
+    for collide in [true, false] {
+        let line = Line::new(&uids, collide);
+        let mut controller = RdmController::new(line.clone(), CONTROLLER);
+        assert_eq!(controller.discover().unwrap(), uids);
+        // Discovery starts by unmuting everyone, so it can be run again.
+        assert_eq!(controller.discover().unwrap(), uids);
+    }
+
+    let mut controller = RdmController::new(Line::new(&[], true), CONTROLLER);
+    assert_eq!(controller.discover().unwrap(), []);
+}
+
+#[test]
+fn configure() {
+    let [a, b] = [Uid::new(0x7A70, 1), Uid::new(0x7A70, 2)];
+    let line = Line::new(&[a, b], true);
+    line.responders.borrow_mut()[0].model_id = 0x0042;
+    let mut controller = RdmController::new(line.clone(), CONTROLLER);
+
+    let info = controller.device_info(a).unwrap();
+    assert_eq!(info.model_id, 0x0042);
+    assert_eq!(info.footprint, 16);
+    assert_eq!(info.start_address, 1);
+
+    controller.set_start_address(a, 100).unwrap();
+    assert_eq!(line.responder(a).start_address, 100);
+    assert_eq!(line.responder(b).start_address, 1);
+    assert_eq!(controller.start_address(a).unwrap(), 100);
+    assert_eq!(controller.device_info(a).unwrap().start_address, 100);
+
+    // The footprint has to fit.
+    assert!(controller.set_start_address(a, 500).is_err());
+    assert!(controller.set_start_address(a, 0).is_err());
+    assert_eq!(line.responder(a).start_address, 100);
+
+    controller.identify(b, true).unwrap();
+    assert!(line.responder(b).identify);
+    assert!(!line.responder(a).identify);
+    controller.identify(b, false).unwrap();
+    assert!(!line.responder(b).identify);
+
+    // Nobody's home.
+    assert!(controller.device_info(Uid::new(0x7A70, 3)).is_err());
+}
+
+#[test]
+fn nacks() {
+    let uid = Uid::new(0x7A70, 1);
+    let mut responder = RdmResponder::new(uid, 16, 1);
+    let mut request = |command_class: CommandClass, pid: u16, data: Vec<u8>| {
+        let request = RdmMessage {
+            dest: uid,
+            src: CONTROLLER,
+            transaction: 7,
+            port_or_response: 1,
+            message_count: 0,
+            sub_device: 0,
+            command_class,
+            pid,
+            data,
+        };
+        let response = RdmMessage::decode(&responder.handle(&request.encode()).unwrap()).unwrap();
+        assert_eq!(response.dest, CONTROLLER);
+        assert_eq!(response.transaction, 7);
+        assert_eq!(response.command_class, command_class.response());
+        assert_eq!(response.port_or_response, ResponseType::NackReason as u8);
+        u16::from_be_bytes(response.data.try_into().unwrap())
+    };
+
+    assert_eq!(request(CommandClass::Get, 0x8000, vec![]), nack::UNKNOWN_PID);
+    assert_eq!(
+        request(CommandClass::Set, pid::DEVICE_INFO, vec![]),
+        nack::UNSUPPORTED_COMMAND_CLASS
+    );
+    assert_eq!(request(CommandClass::Set, pid::DMX_START_ADDRESS, vec![1]), nack::FORMAT_ERROR);
+    assert_eq!(
+        request(CommandClass::Set, pid::IDENTIFY_DEVICE, vec![2]),
+        nack::DATA_OUT_OF_RANGE
+    );
+}
@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+
+use lib::dmx::{DmxPlayer, DmxRecord, DmxRecorder};
+use lib::prelude::*;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ravy-record-{}-{name}.dmx", std::process::id()))
+}
+
+#[test]
+fn round_trip() {
+    let path = temp_path("round-trip");
+    let a = [0, 1, 2, 3];
+    let b = [0, 4, 5, 6];
+    let c = [0; 513];
+
+    let mut recorder = DmxRecorder::create(&path).unwrap();
+    recorder.send_universe(1, &a);
+    recorder.flush();
+    sleep(Duration::from_millis(100));
+    // Unchanged frames are skipped.
+    recorder.send_universe(1, &a);
+    recorder.send_universe(1, &b);
+    recorder.flush();
+    sleep(Duration::from_millis(100));
+    recorder.send_universe(2, &c);
+    recorder.send_universe(1, &a);
+    drop(recorder);
+
+    let mut player = DmxPlayer::open(&path).unwrap();
+    let records = player.advance(Duration::from_millis(50)).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!((records[0].universe, records[0].data.as_slice()), (1, a.as_slice()));
+
+    let records = player.advance(Duration::from_millis(150)).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!((records[0].universe, records[0].data.as_slice()), (1, b.as_slice()));
+    assert!(records[0].time >= Duration::from_millis(100));
+
+    let records = player.advance(Duration::MAX).unwrap();
+    let frames = records.iter().map(|r| (r.universe, r.data.as_slice())).collect::<Vec<_>>();
+    assert_eq!(frames, [(2, c.as_slice()), (1, a.as_slice())]);
+    assert!(records.iter().all(|r| r.time >= Duration::from_millis(200)));
+    assert!(player.finished());
+
+    // Times are the same after a rewind.
+    let all = |player: &mut DmxPlayer| player.advance(Duration::MAX).unwrap();
+    let mut rewound = DmxPlayer::open(&path).unwrap();
+    let first = all(&mut rewound);
+    rewound.rewind().unwrap();
+    assert_eq!(first.len(), 4);
+    assert_eq!(all(&mut rewound), first);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn corrupt() {
+    let path = temp_path("corrupt");
+    let record = |time: u32, data: &[u8]| {
+        let mut record = time.to_le_bytes().to_vec();
+        record.extend_from_slice(&1u16.to_le_bytes());
+        record.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        record.extend_from_slice(data);
+        record
+    };
+
+    std::fs::write(&path, "").unwrap();
+    assert!(DmxPlayer::open(&path).is_err());
+
+    std::fs::write(&path, [b"RAVYDMX0".as_slice(), &record(0, &[0, 1])[..]].concat()).unwrap();
+    assert!(DmxPlayer::open(&path).is_err());
+
+    // Cut off mid-frame, e.g. by a crash. Everything before it still plays.
+    let truncated = [
+        b"RAVYDMX1".as_slice(),
+        &record(0, &[0, 1])[..],
+        &record(10, &[0, 2, 3])[..10],
+    ]
+    .concat();
+    std::fs::write(&path, truncated).unwrap();
+    let mut player = DmxPlayer::open(&path).unwrap();
+    let records = player.advance(Duration::MAX).unwrap();
+    assert_eq!(records, [DmxRecord { time: Duration::ZERO, universe: 1, data: vec![0, 1] }]);
+    assert!(player.finished());
+
+    // Longer than a universe.
+    let oversized = [b"RAVYDMX1".as_slice(), &record(0, &[0; 600])[..]].concat();
+    std::fs::write(&path, oversized).unwrap();
+    assert!(DmxPlayer::open(&path).is_err());
+
+    std::fs::remove_file(&path).unwrap();
+}